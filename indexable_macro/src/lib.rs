@@ -68,6 +68,44 @@ pub fn derive_indexable(input: TokenStream) -> TokenStream {
         }
     }
 
+    // Collect the fields marked with `#[indexable(payload_index = "...")]`
+    let mut payload_indexes = Vec::new();
+    if let syn::Data::Struct(data_struct) = &input.data {
+        for field in &data_struct.fields {
+            let field_ident = match &field.ident {
+                Some(field_ident) => field_ident,
+                None => continue,
+            };
+            for attr in field.attrs.iter() {
+                if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
+                    if !meta_list.path.is_ident("indexable") {
+                        continue;
+                    }
+                    for nested_meta in meta_list.nested.iter() {
+                        if let NestedMeta::Meta(Meta::NameValue(meta_name_value)) = nested_meta {
+                            if !meta_name_value.path.is_ident("payload_index") {
+                                continue;
+                            }
+                            if let syn::Lit::Str(lit_str) = &meta_name_value.lit {
+                                let field_type = field_type_variant(&lit_str.value());
+                                // The index has to be on the key `as_map` stores the field under
+                                let field_name = serde_rename(&field.attrs)
+                                    .unwrap_or_else(|| field_ident.to_string());
+                                payload_indexes.push(quote! {
+                                    (#field_name.to_string(), ::vevtor::qdrant_client::qdrant::FieldType::#field_type)
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if !payload_indexes.is_empty() && has_serde_rename_all(&input.attrs) {
+        panic!("payload_index can't be combined with #[serde(rename_all)]. Rename the indexed fields with #[serde(rename = \"...\")] instead.");
+    }
+
     // Conditional logic in get_id based on type of id_field
     let get_id_impl = if id_is_string {
         quote! {
@@ -109,6 +147,10 @@ pub fn derive_indexable(input: TokenStream) -> TokenStream {
                 self.#collection_field.to_string()
             }
 
+            fn payload_indexes() -> Vec<(String, ::vevtor::qdrant_client::qdrant::FieldType)> {
+                vec![#(#payload_indexes),*]
+            }
+
             fn from_qdrant_payload(payload: &HashMap<String, Value>) -> Result<Self, String> {
                 let json_value: serde_json::Value = serde_json::to_value(payload)
                     .map_err(|err| format!("Failed to convert payload to json {}", err))?;
@@ -160,4 +202,73 @@ pub fn derive_indexable(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+// The name a field is serialized under if it has `#[serde(rename = "...")]`
+// or `#[serde(rename(serialize = "..."))]`
+fn serde_rename(attrs: &[syn::Attribute]) -> Option<String> {
+    for nested_meta in serde_metas(attrs) {
+        match nested_meta {
+            NestedMeta::Meta(Meta::NameValue(meta_name_value))
+                if meta_name_value.path.is_ident("rename") =>
+            {
+                if let syn::Lit::Str(lit_str) = &meta_name_value.lit {
+                    return Some(lit_str.value());
+                }
+            }
+            NestedMeta::Meta(Meta::List(meta_list)) if meta_list.path.is_ident("rename") => {
+                for nested_meta in meta_list.nested.iter() {
+                    if let NestedMeta::Meta(Meta::NameValue(meta_name_value)) = nested_meta {
+                        if meta_name_value.path.is_ident("serialize") {
+                            if let syn::Lit::Str(lit_str) = &meta_name_value.lit {
+                                return Some(lit_str.value());
+                            }
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+fn has_serde_rename_all(attrs: &[syn::Attribute]) -> bool {
+    serde_metas(attrs)
+        .iter()
+        .any(|nested_meta| match nested_meta {
+            NestedMeta::Meta(meta) => meta.path().is_ident("rename_all"),
+            NestedMeta::Lit(_) => false,
+        })
+}
+
+// Everything inside the `#[serde(...)]` attributes
+fn serde_metas(attrs: &[syn::Attribute]) -> Vec<NestedMeta> {
+    attrs
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(meta_list)) if meta_list.path.is_ident("serde") => Some(meta_list.nested),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+// Maps the value of a `payload_index` attribute to a Qdrant `FieldType` variant
+fn field_type_variant(value: &str) -> Ident {
+    let variant = match value {
+        "keyword" => "Keyword",
+        "integer" => "Integer",
+        "float" => "Float",
+        "geo" => "Geo",
+        "text" => "Text",
+        "bool" => "Bool",
+        "datetime" => "Datetime",
+        "uuid" => "Uuid",
+        other => panic!(
+            "Unknown payload_index type '{}'. Expected one of: keyword, integer, float, geo, text, bool, datetime, uuid.",
+            other
+        ),
+    };
+    Ident::new(variant, proc_macro2::Span::call_site())
+}
+
 // Helper function for ID hashing
@@ -1,41 +1,167 @@
-use std::{collections::HashMap, iter::zip};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::zip,
+    path::Path,
+};
 
-use qdrant_client::{qdrant::HealthCheckReply, Payload, QdrantError};
-use tokio::sync::RwLock;
+use futures_util::{future::join_all, stream, Stream, StreamExt};
+use qdrant_client::{
+    qdrant::{
+        points_selector::PointsSelectorOneOf,
+        points_update_operation::{
+            DeletePayload, DeletePoints, Operation, OverwritePayload, PointStructList, SetPayload,
+        },
+        CollectionStatus, Distance, FieldType, Filter, HealthCheckReply, PointId, PointStruct,
+        PointsIdsList, PointsOperationResponse, PointsSelector, PointsUpdateOperation,
+        ReadConsistencyType, SnapshotDescription, UpdateStatus, Value,
+    },
+    Payload, QdrantError,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
+    sync::RwLock,
+};
 
+use super::registry::{self, CollectionRecord, REGISTRY_COLLECTION};
 use crate::{
-    indexer_api::traits::indexable::{Indexable, IntoPayload},
-    vector_db::{db::api::QdrantApi, embeddings::generator::EmbeddingsGenerator},
+    indexer_api::{
+        models::{
+            collection_models::{CollectionConfig, CollectionInfoModel},
+            consistency_models::WriteOptions,
+            point_models::{ExportedPoint, PointOperation, PointSelector},
+        },
+        traits::{
+            indexable::{Indexable, IntoPayload},
+            reranker::Reranker,
+        },
+        util::{
+            hashing::string_to_u64,
+            mmr::mmr_select,
+            naming::{validate_collection_name, VERSION_SEPARATOR},
+        },
+    },
+    vector_db::{
+        db::{
+            api::{is_already_exists, vector_params, QdrantApi},
+            builders::with_collection::WithCollectionBuilder,
+        },
+        embeddings::generator::EmbeddingsGenerator,
+    },
 };
 
 pub struct FileVectorDbManager {
     qdrant: QdrantApi,
     generator: EmbeddingsGenerator,
+    /// Collections created or verified by this process, whose upserts can skip both
     known_collections: RwLock<Vec<String>>,
+    /// Payload indexes known to exist, as `(collection, field)` pairs
+    known_indexes: RwLock<HashSet<(CollectionName, String)>>,
+    collection_configs: RwLock<HashMap<CollectionName, CollectionConfig>>,
+    namespace: Option<String>,
 }
 
 type CollectionName = String;
 type ID = u64;
 
+/// How many items are re-embedded and upserted at a time during a migration
+const MIGRATION_BATCH_SIZE: u32 = 256;
+
+/// How many points are read or upserted at a time during an export or import
+const EXPORT_BATCH_SIZE: u32 = 256;
+
 impl FileVectorDbManager {
-    pub fn new(url: &str) -> Self {
-        let qdrant = QdrantApi::new(url);
+    pub fn new(url: &str, namespace: Option<&str>) -> Self {
+        let qdrant = QdrantApi::new(url, namespace);
         let generator = EmbeddingsGenerator::new();
         Self {
             qdrant,
             generator,
             known_collections: RwLock::new(Vec::new()),
+            known_indexes: RwLock::new(HashSet::new()),
+            collection_configs: RwLock::new(HashMap::new()),
+            namespace: namespace.map(|namespace| namespace.to_string()),
         }
     }
 
-    pub async fn reset_all(&self) {
-        let collections = self.qdrant.list_collections().await;
-        self.qdrant
-            .delete_collections(&collections.iter().map(|x| x.as_str()).collect())
+    /// The collections that vevtor created and may delete: every collection in the namespace,
+    /// or without one, the collections that have a registry record. The registry comes last.
+    pub async fn owned_collections(&self) -> Result<Vec<CollectionName>, String> {
+        let mut collections = self.qdrant.list_collections().await;
+
+        if self.namespace.is_none() {
+            let recorded = registry::recorded_collections(&self.qdrant)
+                .await
+                .map_err(|err| format!("Error reading collection registry: {}", err))?;
+            collections.retain(|collection| {
+                collection == REGISTRY_COLLECTION || recorded.contains(collection)
+            });
+        }
+
+        // The registry has to outlive the records that are removed from it
+        collections.sort_by_key(|collection| collection == REGISTRY_COLLECTION);
+        Ok(collections)
+    }
+
+    /// Deletes every collection returned by `owned_collections`,
+    /// leaving collections created by anything else alone.
+    ///
+    /// The registry is only deleted once everything else was. Otherwise it is kept, along with
+    /// the records of the collections that failed, so that the next reset can still find them.
+    pub async fn reset_owned(
+        &self,
+    ) -> Result<Vec<(CollectionName, Result<(), QdrantError>)>, String> {
+        let collections = self.owned_collections().await?;
+        let aliases = self.qdrant.list_aliases().await.unwrap_or_default();
+        let deleted = self
+            .qdrant
+            .delete_collections(
+                &collections
+                    .iter()
+                    .filter(|collection| *collection != REGISTRY_COLLECTION)
+                    .map(|x| x.as_str())
+                    .collect::<Vec<_>>(),
+            )
             .await;
+
+        let mut results = Vec::new();
+        for (collection, result) in deleted {
+            let mut result = result.map(|_| ());
+            if result.is_ok() {
+                result = registry::forget(&self.qdrant, &collection).await;
+                // Qdrant deletes the aliases of a deleted collection along with it
+                for (alias, _) in aliases.iter().filter(|(_, target)| *target == collection) {
+                    if result.is_ok() {
+                        result = registry::forget(&self.qdrant, alias).await;
+                    }
+                }
+            }
+            results.push((collection, result));
+        }
+
+        let owns_registry = collections
+            .iter()
+            .any(|collection| collection == REGISTRY_COLLECTION);
+        if owns_registry && results.iter().all(|(_, result)| result.is_ok()) {
+            let result = self
+                .qdrant
+                .delete_collection(REGISTRY_COLLECTION)
+                .await
+                .map(|_| ());
+            results.push((REGISTRY_COLLECTION.to_string(), result));
+        }
+        self.refresh_known_collections().await;
+
+        Ok(results)
     }
 
-    pub async fn insert_many<T>(&self, entries: Vec<T>) -> Result<(), String>
+    /// Embeds and upserts the entries into their collections.
+    /// `write_options` overrides the default write options for these upserts.
+    pub async fn insert_many<T>(
+        &self,
+        entries: Vec<T>,
+        write_options: Option<WriteOptions>,
+    ) -> Result<(), String>
     where
         T: Indexable + IntoPayload,
     {
@@ -46,14 +172,17 @@ impl FileVectorDbManager {
 
         // Optional check?:
         for (collection_name, _) in batches.iter() {
-            self.ensure_collection_exists(collection_name)
+            self.validate_collection_name(collection_name)?;
+            self.ensure_collection_exists(collection_name, &T::payload_indexes())
                 .await
                 .map_err(|err| format!("Error ensuring collection exists: {}", err))?;
         }
 
+        let mut errors = Vec::new();
         for (collection_name, file_group) in batches {
-            self.qdrant
-                .with_collection(&collection_name) // Use specific collection
+            let count = file_group.len();
+            let failures = self
+                .with_collection_writing(&collection_name, write_options) // Use specific collection
                 .insert_many(
                     file_group
                         .into_iter()
@@ -66,34 +195,200 @@ impl FileVectorDbManager {
                         .collect(),
                 )
                 .await;
+
+            if let Some((id, err)) = failures.first() {
+                errors.push(format!(
+                    "{} of {} items failed to upsert into '{}', e.g. item {}: {}",
+                    failures.len(),
+                    count,
+                    collection_name,
+                    id,
+                    err
+                ));
+            }
         }
 
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
     }
 
-    pub async fn delete_many(&self, ids: Vec<(CollectionName, ID)>) {
+    pub async fn delete_many(
+        &self,
+        ids: Vec<(CollectionName, ID)>,
+    ) -> Vec<(CollectionName, Result<UpdateStatus, QdrantError>)> {
         let groups = self.group_ids(ids);
 
+        let mut results = Vec::new();
         for (collection, ids) in groups {
-            if let Err(err) = self
+            let result = self
                 .qdrant
                 .with_collection(&collection)
                 .remove_many(ids)
                 .await
-            {
-                println!(
-                    "Error deleting ids from collection '{}': {}",
-                    collection, err
-                )
-            }
+                .map(|response| operation_status(&response));
+            results.push((collection, result));
         }
+        results
+    }
+
+    pub async fn delete_by_filter(
+        &self,
+        collection: &str,
+        filter: Filter,
+    ) -> Result<UpdateStatus, QdrantError> {
+        self.qdrant
+            .with_collection(collection)
+            .remove_by_filter(filter)
+            .await
+            .map(|response| operation_status(&response))
+    }
+
+    pub async fn update_payload(
+        &self,
+        collection: &str,
+        selector: PointSelector,
+        payload: Payload,
+    ) -> Result<UpdateStatus, QdrantError> {
+        self.qdrant
+            .with_collection(collection)
+            .set_payload(into_points_selector(selector), payload)
+            .await
+            .map(|response| operation_status(&response))
+    }
+
+    pub async fn overwrite_payload(
+        &self,
+        collection: &str,
+        selector: PointSelector,
+        payload: Payload,
+    ) -> Result<UpdateStatus, QdrantError> {
+        self.qdrant
+            .with_collection(collection)
+            .overwrite_payload(into_points_selector(selector), payload)
+            .await
+            .map(|response| operation_status(&response))
     }
 
+    pub async fn delete_payload_keys(
+        &self,
+        collection: &str,
+        selector: PointSelector,
+        keys: Vec<String>,
+    ) -> Result<UpdateStatus, QdrantError> {
+        self.qdrant
+            .with_collection(collection)
+            .delete_payload_keys(into_points_selector(selector), keys)
+            .await
+            .map(|response| operation_status(&response))
+    }
+
+    /// Applies the operations to one collection in order, in a single request, creating the
+    /// collection if needed. All upserted items are embedded up front, so a failed embedding
+    /// leaves the collection untouched. Returns the status of each operation.
+    pub async fn apply_batch<T>(
+        &self,
+        collection: &str,
+        operations: Vec<PointOperation<T>>,
+        write_options: Option<WriteOptions>,
+    ) -> Result<Vec<UpdateStatus>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.validate_collection_name(collection)?;
+        let items: Vec<&T> = operations
+            .iter()
+            .flat_map(|operation| match operation {
+                PointOperation::Upsert(items) => items.iter().collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+        if let Some(item) = items.iter().find(|item| item.collection() != collection) {
+            return Err(format!(
+                "Item {} belongs to collection '{}', not '{}'",
+                item.get_id(),
+                item.collection(),
+                collection
+            ));
+        }
+        let mut embeddings = self
+            .generator
+            .embed_many(items.iter().map(|item| item.embed_label()).collect())
+            .map_err(|err| format!("Error generating embeddings: {}", err))?
+            .into_iter();
+
+        self.ensure_collection_exists(collection, &T::payload_indexes())
+            .await
+            .map_err(|err| format!("Error ensuring collection exists: {}", err))?;
+
+        let operations: Vec<PointsUpdateOperation> = operations
+            .into_iter()
+            .map(|operation| {
+                let operation = match operation {
+                    PointOperation::Upsert(items) => Operation::Upsert(PointStructList {
+                        points: zip(items, embeddings.by_ref())
+                            .map(|(item, embedding)| {
+                                let id = item.get_id();
+                                let payload: Payload = item.into();
+                                PointStruct::new(id, embedding, payload)
+                            })
+                            .collect(),
+                        ..Default::default()
+                    }),
+                    PointOperation::Delete(selector) => Operation::DeletePoints(DeletePoints {
+                        points: Some(into_selector(selector)),
+                        ..Default::default()
+                    }),
+                    PointOperation::SetPayload(selector, payload) => {
+                        Operation::SetPayload(SetPayload {
+                            payload: payload.into(),
+                            points_selector: Some(into_selector(selector)),
+                            ..Default::default()
+                        })
+                    }
+                    PointOperation::OverwritePayload(selector, payload) => {
+                        Operation::OverwritePayload(OverwritePayload {
+                            payload: payload.into(),
+                            points_selector: Some(into_selector(selector)),
+                            ..Default::default()
+                        })
+                    }
+                    PointOperation::DeletePayloadKeys(selector, keys) => {
+                        Operation::DeletePayload(DeletePayload {
+                            keys,
+                            points_selector: Some(into_selector(selector)),
+                            ..Default::default()
+                        })
+                    }
+                };
+                PointsUpdateOperation {
+                    operation: Some(operation),
+                }
+            })
+            .collect();
+
+        self.with_collection_writing(collection, write_options)
+            .update_batch(operations)
+            .await
+            .map(|response| {
+                response
+                    .result
+                    .iter()
+                    .map(|result| UpdateStatus::try_from(result.status).unwrap_or_default())
+                    .collect()
+            })
+            .map_err(|err| format!("Error applying batch to '{}': {}", collection, err))
+    }
+
+    /// `read_consistency` overrides the default read consistency for this search.
     pub async fn search<T>(
         &self,
         query: &str,
         collection: &str,
         top_k: u64,
+        read_consistency: Option<ReadConsistencyType>,
     ) -> Result<Vec<(T, f32)>, String>
     where
         T: Indexable + IntoPayload,
@@ -104,13 +399,389 @@ impl FileVectorDbManager {
             std::collections::HashMap<String, qdrant_client::qdrant::Value>,
             f32,
         )> = self
+            .with_collection_reading(collection, read_consistency)
+            .search(test, top_k)
+            .await
+            .map_err(|err| format!("Search error: {}", err))?;
+
+        Ok(Self::parse_results(search))
+    }
+
+    /// Fetches `fetch_k` candidates along with their vectors and re-ranks them
+    /// client-side with maximal marginal relevance, keeping `top_k` diverse results.
+    pub async fn search_mmr<T>(
+        &self,
+        query: &str,
+        collection: &str,
+        top_k: u64,
+        fetch_k: u64,
+        lambda: f32,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let embedding = self.generator.embed(query)?;
+
+        let mut candidates = self
             .qdrant
             .with_collection(collection)
-            .search(test, top_k)
+            .search_with_vectors(embedding.clone(), fetch_k.max(top_k))
+            .await
+            .map_err(|err| format!("Search error: {}", err))?;
+
+        let picked = mmr_select(
+            &embedding,
+            &candidates
+                .iter()
+                .map(|(_, _, vector)| vector.as_slice())
+                .collect::<Vec<_>>(),
+            lambda,
+            top_k as usize,
+        );
+
+        let results = picked
+            .into_iter()
+            .map(|index| {
+                let (payload, score, _) = std::mem::take(&mut candidates[index]);
+                (payload, score)
+            })
+            .collect();
+
+        Ok(Self::parse_results(results))
+    }
+
+    /// Fetches `candidates` results and re-scores them with `reranker`
+    /// against each item's `embed_label`, keeping the best `top_k`.
+    pub async fn search_reranked<T>(
+        &self,
+        query: &str,
+        collection: &str,
+        top_k: u64,
+        candidates: u64,
+        reranker: &dyn Reranker,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let results = self
+            .search::<T>(query, collection, candidates.max(top_k), None)
+            .await?;
+
+        let scores = reranker.rerank(
+            query,
+            results.iter().map(|(item, _)| item.embed_label()).collect(),
+        )?;
+
+        let mut reranked: Vec<(T, f32)> = zip(results, scores)
+            .map(|((item, _), score)| (item, score))
+            .collect();
+        reranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        reranked.truncate(top_k as usize);
+        Ok(reranked)
+    }
+
+    /// Searches a collection and groups the hits by a payload field.
+    /// Groups are returned best-first as `(group value, hits)` pairs.
+    pub async fn search_groups<T>(
+        &self,
+        query: &str,
+        collection: &str,
+        group_by: &str,
+        group_size: u32,
+        groups: u32,
+    ) -> Result<Vec<(String, Vec<(T, f32)>)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let embedding = self.generator.embed(query)?;
+
+        let groups = self
+            .qdrant
+            .with_collection(collection)
+            .search_groups(embedding, group_by, group_size, groups)
             .await
             .map_err(|err| format!("Search error: {}", err))?;
 
-        Ok(search
+        Ok(groups
+            .into_iter()
+            .map(|(key, hits)| (key, Self::parse_results(hits)))
+            .collect())
+    }
+
+    /// Searches with a precomputed embedding instead of embedding a query string.
+    /// The vector must match the dimension the collection was created with.
+    pub async fn search_by_vector<T>(
+        &self,
+        vector: Vec<f32>,
+        collection: &str,
+        top_k: u64,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let dim = self
+            .qdrant
+            .vector_dim(&self.resolve_collection(collection).await?)
+            .await
+            .map_err(|err| format!("Error reading collection config: {}", err))?;
+
+        if let Some(dim) = dim {
+            if vector.len() as u64 != dim {
+                return Err(format!(
+                    "Vector has {} dims but collection '{}' expects {}",
+                    vector.len(),
+                    collection,
+                    dim
+                ));
+            }
+        }
+
+        let search = self
+            .qdrant
+            .with_collection(collection)
+            .search(vector, top_k)
+            .await
+            .map_err(|err| format!("Search error: {}", err))?;
+
+        Ok(Self::parse_results(search))
+    }
+
+    /// Embeds the query once and searches every collection concurrently.
+    /// Results are merged by score and truncated to `top_k` overall, so every collection
+    /// must use the same similarity metric: `Cosine` or `Dot`. With `Euclid` and `Manhattan`
+    /// lower scores are better, which the merge doesn't support.
+    pub async fn search_collections<T>(
+        &self,
+        query: &str,
+        collections: &[String],
+        top_k: u64,
+    ) -> Result<Vec<(T, f32, CollectionName)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.check_mergeable_distances(collections).await?;
+        let embedding = self.generator.embed(query)?;
+
+        let searches = collections.iter().map(|collection| {
+            let embedding = embedding.clone();
+            async move {
+                self.qdrant
+                    .with_collection(collection)
+                    .search(embedding, top_k)
+                    .await
+                    .map(|results| (collection, results))
+                    .map_err(|err| format!("Search error in collection '{}': {}", collection, err))
+            }
+        });
+
+        let mut merged: Vec<(T, f32, CollectionName)> = Vec::new();
+        for result in join_all(searches).await {
+            let (collection, results) = result?;
+            merged.extend(results.into_iter().filter_map(|(payload, score)| {
+                // Ignore entries that couldn't be parsed from the payload
+                T::from_qdrant_payload(&payload)
+                    .ok()
+                    .map(|model| (model, score, collection.clone()))
+            }));
+        }
+
+        merged.sort_by(|a, b| b.1.total_cmp(&a.1));
+        merged.truncate(top_k as usize);
+        Ok(merged)
+    }
+
+    /// Fails unless all `collections` use the same metric, and one whose scores are higher
+    /// for closer matches.
+    async fn check_mergeable_distances(&self, collections: &[String]) -> Result<(), String> {
+        let distances = join_all(
+            collections
+                .iter()
+                .map(|collection| self.collection_distance(collection)),
+        )
+        .await;
+
+        let mut merged: Option<(&String, Distance)> = None;
+        for (collection, distance) in zip(collections, distances) {
+            let Some(distance) = distance? else {
+                continue;
+            };
+            if matches!(distance, Distance::Euclid | Distance::Manhattan) {
+                return Err(format!(
+                    "Collection '{}' uses the {} distance, where lower scores are better, so its results can't be merged with other collections",
+                    collection,
+                    distance.as_str_name()
+                ));
+            }
+            match merged {
+                Some((other, other_distance)) if other_distance != distance => {
+                    return Err(format!(
+                        "Collections '{}' ({}) and '{}' ({}) use different distances, so their scores can't be compared",
+                        other,
+                        other_distance.as_str_name(),
+                        collection,
+                        distance.as_str_name()
+                    ));
+                }
+                Some(_) => {}
+                None => merged = Some((collection, distance)),
+            }
+        }
+        Ok(())
+    }
+
+    /// The distance metric of the collection behind `name`, if it uses a single unnamed vector.
+    async fn collection_distance(&self, name: &str) -> Result<Option<Distance>, String> {
+        let collection = self.resolve_collection(name).await?;
+        let info = self
+            .qdrant
+            .collection_info(&collection)
+            .await
+            .map_err(|err| format!("Error getting info of collection '{}': {}", name, err))?;
+        Ok(info
+            .as_ref()
+            .and_then(vector_params)
+            .and_then(|params| Distance::try_from(params.distance).ok()))
+    }
+
+    /// Embeds every query in one pass and issues one batch search per collection.
+    /// `queries` are `(collection, query)` pairs; results are returned in input order.
+    pub async fn search_batch<T>(
+        &self,
+        queries: &[(&str, &str)],
+        top_k: u64,
+    ) -> Result<Vec<Vec<(T, f32)>>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let embeddings = self
+            .generator
+            .embed_many(queries.iter().map(|(_, query)| *query).collect())
+            .map_err(|err| format!("Error generating embeddings: {}", err))?;
+
+        // Remember the input position of every query so results can be put back in order
+        let mut batches: HashMap<&str, (Vec<usize>, Vec<Vec<f32>>)> = HashMap::new();
+        for (index, ((collection, _), embedding)) in zip(queries, embeddings).enumerate() {
+            let batch = batches.entry(collection).or_default();
+            batch.0.push(index);
+            batch.1.push(embedding);
+        }
+
+        let mut results: Vec<Vec<(T, f32)>> = queries.iter().map(|_| Vec::new()).collect();
+        for (collection, (indices, embeddings)) in batches {
+            let batch = self
+                .qdrant
+                .with_collection(collection)
+                .search_batch(embeddings, top_k)
+                .await
+                .map_err(|err| format!("Search error in collection '{}': {}", collection, err))?;
+
+            for (index, search) in zip(indices, batch) {
+                results[index] = Self::parse_results(search);
+            }
+        }
+
+        Ok(results)
+    }
+
+    pub async fn recommend<T>(
+        &self,
+        collection: &str,
+        positive: Vec<ID>,
+        negative: Vec<ID>,
+        top_k: u64,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let results = self
+            .qdrant
+            .with_collection(collection)
+            .recommend(positive, negative, top_k)
+            .await
+            .map_err(|err| format!("Recommend error: {}", err))?;
+
+        Ok(Self::parse_results(results))
+    }
+
+    /// Fetches items by id. The result lines up with `ids`, with `None` for ids that
+    /// aren't in the collection or whose payload couldn't be decoded.
+    pub async fn get_many<T>(
+        &self,
+        collection: &str,
+        ids: Vec<ID>,
+    ) -> Result<Vec<Option<T>>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let mut found: HashMap<ID, HashMap<String, Value>> = self
+            .qdrant
+            .with_collection(collection)
+            .get_many(ids.clone())
+            .await
+            .map_err(|err| format!("Error retrieving points: {}", err))?
+            .into_iter()
+            .collect();
+
+        Ok(ids
+            .iter()
+            .map(|id| {
+                found
+                    .remove(id)
+                    .and_then(|payload| T::from_qdrant_payload(&payload).ok())
+            })
+            .collect())
+    }
+
+    /// Pages through every item in a collection that matches `filter`.
+    /// An item whose payload can't be decoded is yielded as an error and the stream goes on
+    /// with the next one; a failed page ends the stream with an error.
+    pub fn scroll<'a, T>(
+        &'a self,
+        collection: &'a str,
+        filter: Option<Filter>,
+        page_size: u32,
+    ) -> impl Stream<Item = Result<T, String>> + 'a
+    where
+        T: Indexable + IntoPayload,
+    {
+        // The state is the offset of the next page to read, or `None` once the last page was read
+        let pages = stream::unfold(Some(None), move |offset| {
+            let filter = filter.clone();
+            async move {
+                let page = self
+                    .qdrant
+                    .with_collection(collection)
+                    .scroll(filter, page_size, offset?)
+                    .await;
+
+                match page {
+                    Ok((payloads, next)) => Some((Ok(payloads), next.map(Some))),
+                    Err(err) => Some((Err(format!("Scroll error: {}", err)), None)),
+                }
+            }
+        });
+
+        pages.flat_map(move |page| {
+            let items: Vec<Result<T, String>> = match page {
+                Ok(payloads) => payloads
+                    .iter()
+                    .map(|payload| {
+                        T::from_qdrant_payload(payload).map_err(|err| {
+                            format!("An item in '{}' can't be decoded: {}", collection, err)
+                        })
+                    })
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            };
+            stream::iter(items)
+        })
+    }
+
+    fn parse_results<T>(results: Vec<(HashMap<String, Value>, f32)>) -> Vec<(T, f32)>
+    where
+        T: Indexable,
+    {
+        results
             .into_iter()
             .filter_map(|(payload, score)| {
                 // Ignore entries that couldn't be parsed from the payload
@@ -119,7 +790,7 @@ impl FileVectorDbManager {
                 }
                 None
             })
-            .collect())
+            .collect()
     }
 
     fn generate_embeddings<T>(&self, entries: &[T]) -> Result<Vec<Vec<f32>>, String>
@@ -161,31 +832,713 @@ impl FileVectorDbManager {
         grouped_ids
     }
 
-    pub async fn ensure_collection_exists(&self, name: &str) -> Result<(), QdrantError> {
+    /// Sets the write options used by every write that doesn't override them.
+    pub fn set_write_options(&self, write_options: WriteOptions) {
+        self.qdrant.set_write_options(write_options)
+    }
+
+    /// Sets the read consistency used by every read that doesn't override it.
+    pub fn set_read_consistency(&self, read_consistency: Option<ReadConsistencyType>) {
+        self.qdrant.set_read_consistency(read_consistency)
+    }
+
+    fn with_collection_writing(
+        &self,
+        collection: &str,
+        write_options: Option<WriteOptions>,
+    ) -> WithCollectionBuilder<'_> {
+        let builder = self.qdrant.with_collection(collection);
+        match write_options {
+            Some(write_options) => builder.write_options(write_options),
+            None => builder,
+        }
+    }
+
+    fn with_collection_reading(
+        &self,
+        collection: &str,
+        read_consistency: Option<ReadConsistencyType>,
+    ) -> WithCollectionBuilder<'_> {
+        let builder = self.qdrant.with_collection(collection);
+        match read_consistency {
+            Some(read_consistency) => builder.read_consistency(Some(read_consistency)),
+            None => builder,
+        }
+    }
+
+    /// Registers the config used when `name` has to be created.
+    /// Collections without one use `CollectionConfig::default()`.
+    pub async fn set_collection_config(&self, name: &str, config: CollectionConfig) {
+        self.collection_configs
+            .write()
+            .await
+            .insert(name.to_string(), config);
+    }
+
+    pub fn validate_collection_name(&self, name: &str) -> Result<(), String> {
+        validate_collection_name(name, self.namespace.as_deref())
+    }
+
+    /// Creates the collection with its registered config if it doesn't exist yet,
+    /// as `{name}_v1` behind a `name` alias so that it can be migrated without downtime,
+    /// then creates a field index for every entry in `payload_indexes` that this
+    /// process hasn't seen yet, whoever created the collection.
+    ///
+    /// A collection that already exists is verified against the current embedding model
+    /// the first time it is seen, so a mismatch fails here instead of on every upsert.
+    pub async fn ensure_collection_exists(
+        &self,
+        name: &str,
+        payload_indexes: &[(String, FieldType)],
+    ) -> Result<(), String> {
+        self.ensure_collection(name).await?;
+        self.ensure_payload_indexes(name, payload_indexes)
+            .await
+            .map_err(|err| format!("Error creating payload index on '{}': {}", name, err))
+    }
+
+    /// The collection is only cached once every step succeeded. The steps are idempotent,
+    /// so a failed call is completed by the next one.
+    async fn ensure_collection(&self, name: &str) -> Result<(), String> {
         let name_str = name.to_string();
 
-        if !self.known_collections.read().await.contains(&name_str) {
-            // Only refresh and create if the collection is not known
-            self.refresh_known_collections().await;
+        if self.known_collections.read().await.contains(&name_str) {
+            return Ok(());
+        }
+
+        // Holding the write lock makes concurrent index workers wait for each other
+        // instead of all trying to create the same collection
+        let mut known_collections = self.known_collections.write().await;
+        if known_collections.contains(&name_str) {
+            // Created by another worker while we were waiting for the lock
+            return Ok(());
+        }
+
+        // Only look the collection up and create it if it is not known
+        let mut exists = self.find_collection(name).await?.is_some();
+        if !exists {
+            let config = self
+                .collection_configs
+                .read()
+                .await
+                .get(name)
+                .cloned()
+                .unwrap_or_default();
+
+            let collection = first_version(name);
+
+            // Recorded first, so a collection is never left without a record. A record
+            // without a collection is harmless and overwritten by the next attempt
+            let record = CollectionRecord {
+                model: self.generator.model_id.clone(),
+                dimension: self.generator.embedding_dim_len,
+            };
+            for recorded in [name, collection.as_str()] {
+                registry::record(&self.qdrant, recorded, &record)
+                    .await
+                    .map_err(|err| {
+                        format!("Error updating registry for '{}': {}", recorded, err)
+                    })?;
+            }
+
+            match self
+                .qdrant
+                .create_collection(&collection, self.generator.embedding_dim_len, &config)
+                .await
+            {
+                Ok(_) => println!("Created collection: {}", collection),
+                // Another process created it after we listed the collections,
+                // or an earlier attempt failed before creating the alias
+                Err(err) if is_already_exists(&err) => exists = true,
+                Err(err) => {
+                    return Err(format!(
+                        "Error creating collection '{}': {}",
+                        collection, err
+                    ))
+                }
+            }
+
+            // Replacing the alias is a no-op if another process already created it
             self.qdrant
-                .create_collection(name, self.generator.embedding_dim_len)
+                .create_alias(&collection, name)
+                .await
+                .map_err(|err| {
+                    format!(
+                        "Error pointing alias '{}' at '{}': {}",
+                        name, collection, err
+                    )
+                })?;
+        }
+
+        if exists {
+            self.verify_collection(name).await?;
+        }
+
+        known_collections.push(name_str);
+        Ok(())
+    }
+
+    /// Creating an index that already exists is a no-op in Qdrant,
+    /// so each index is created once per process without checking for it first.
+    async fn ensure_payload_indexes(
+        &self,
+        name: &str,
+        payload_indexes: &[(String, FieldType)],
+    ) -> Result<(), QdrantError> {
+        for (field, field_type) in payload_indexes {
+            let key = (name.to_string(), field.clone());
+            if self.known_indexes.read().await.contains(&key) {
+                continue;
+            }
+
+            self.qdrant
+                .with_collection(name)
+                .create_field_index(field, *field_type)
                 .await?;
-            println!("Created collection: {}", name);
+            self.known_indexes.write().await.insert(key);
+        }
+        Ok(())
+    }
+
+    /// Checks that the collection was created for the current embedding model.
+    /// Collections created before the registry existed are only checked by dimension.
+    pub async fn verify_collection(&self, name: &str) -> Result<(), String> {
+        let model = &self.generator.model_id;
+        let dimension = self.generator.embedding_dim_len;
+
+        let collection = self.resolve_collection(name).await?;
+        let actual_dimension = self
+            .qdrant
+            .vector_dim(&collection)
+            .await
+            .map_err(|err| format!("Error reading config of collection '{}': {}", name, err))?;
+        if let Some(actual_dimension) = actual_dimension {
+            if actual_dimension != dimension {
+                return Err(format!(
+                    "Collection '{}' stores {}-dim vectors, but the embedding model '{}' produces {}-dim vectors",
+                    name, actual_dimension, model, dimension
+                ));
+            }
+        }
+
+        let record = registry::lookup(&self.qdrant, name)
+            .await
+            .map_err(|err| format!("Error reading registry for collection '{}': {}", name, err))?;
+        if let Some(record) = record {
+            if &record.model != model || record.dimension != dimension {
+                return Err(format!(
+                    "Collection '{}' was created with embedding model '{}' ({} dims), but the current model is '{}' ({} dims)",
+                    name, record.model, record.dimension, model, dimension
+                ));
+            }
         }
 
         Ok(())
     }
 
+    /// Verifies every collection returned by `owned_collections`, reporting all mismatches
+    /// at once. Collections of other apps sharing the server are left alone.
+    pub async fn verify_collections(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        let collections = self.owned_collections().await?;
+        for collection in collections
+            .iter()
+            .filter(|collection| *collection != REGISTRY_COLLECTION)
+        {
+            if let Err(err) = self.verify_collection(collection).await {
+                errors.push(err);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    pub async fn count(
+        &self,
+        collection: &str,
+        filter: Option<Filter>,
+        exact: bool,
+    ) -> Result<u64, QdrantError> {
+        self.qdrant
+            .with_collection(collection)
+            .count(filter, exact)
+            .await
+    }
+
+    pub async fn collection_info(&self, name: &str) -> Result<CollectionInfoModel, String> {
+        let info = self
+            .qdrant
+            .collection_info(&self.resolve_collection(name).await?)
+            .await
+            .map_err(|err| format!("Error getting collection info: {}", err))?
+            .ok_or(format!("No info returned for collection '{}'", name))?;
+
+        let params = vector_params(&info);
+        Ok(CollectionInfoModel {
+            points_count: info.points_count.unwrap_or_default(),
+            indexed_vectors_count: info.indexed_vectors_count.unwrap_or_default(),
+            status: CollectionStatus::try_from(info.status).unwrap_or_default(),
+            vector_size: params.map(|params| params.size),
+            distance: params.and_then(|params| Distance::try_from(params.distance).ok()),
+        })
+    }
+
+    /// Lists collections by their logical names: aliases are listed in place of
+    /// the versioned collections they point to.
     pub async fn list_collections(&self) -> Vec<String> {
-        self.qdrant.list_collections().await
+        let aliases = self.qdrant.list_aliases().await.unwrap_or_default();
+        let mut collections = self.qdrant.list_collections().await;
+        collections.retain(|collection| {
+            collection != REGISTRY_COLLECTION
+                && !aliases.iter().any(|(_, target)| target == collection)
+        });
+        collections.extend(aliases.into_iter().map(|(alias, _)| alias));
+        collections
+    }
+
+    /// The collection that a logical name currently refers to.
+    pub async fn resolve_collection(&self, name: &str) -> Result<CollectionName, String> {
+        self.find_collection(name)
+            .await?
+            .ok_or_else(|| format!("Collection '{}' does not exist", name))
+    }
+
+    /// Same as `resolve_collection`, but `None` if neither an alias nor a collection is
+    /// called `name`. Any other failure is still an error.
+    async fn find_collection(&self, name: &str) -> Result<Option<CollectionName>, String> {
+        let aliases = self
+            .qdrant
+            .list_aliases()
+            .await
+            .map_err(|err| format!("Error listing aliases: {}", err))?;
+
+        if let Some((_, target)) = aliases.into_iter().find(|(alias, _)| alias == name) {
+            return Ok(Some(target));
+        }
+
+        let exists = self
+            .qdrant
+            .collection_exists(name)
+            .await
+            .map_err(|err| format!("Error checking collection '{}': {}", name, err))?;
+        Ok(exists.then(|| name.to_string()))
+    }
+
+    /// Re-embeds every item of the collection behind `name` into a new versioned collection,
+    /// then points the `name` alias at it and deletes the old one. Searches keep hitting the
+    /// old collection until the swap. Returns the name of the new collection.
+    ///
+    /// Items indexed while the copy is running may be missing from the new collection.
+    /// Collections created by `ensure_collection_exists` are always behind an alias. A plain
+    /// collection created before that has to be deleted before the alias can take its name,
+    /// so it is briefly unavailable during its first migration.
+    ///
+    /// If the copy fails, the new collection is deleted again and the live one is untouched.
+    pub async fn migrate_collection<T>(&self, name: &str) -> Result<CollectionName, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let live = self.resolve_collection(name).await?;
+        let shadow = next_version(name, &live);
+
+        // Left behind by a migration that was interrupted before it could clean up
+        self.drop_leftover(&shadow).await?;
+
+        if let Err(err) = self.copy_collection::<T>(name, &live, &shadow).await {
+            if let Err(cleanup_err) = self.drop_collection(&shadow).await {
+                return Err(format!("{}\nCleaning up also failed: {}", err, cleanup_err));
+            }
+            return Err(err);
+        }
+
+        self.swap_alias(name, Some(&live), &shadow).await?;
+        Ok(shadow)
+    }
+
+    /// Points the `name` alias at `target` and deletes `live`, the collection it pointed to.
+    async fn swap_alias(&self, name: &str, live: Option<&str>, target: &str) -> Result<(), String> {
+        if live == Some(name) {
+            // An alias can't share its name with a collection
+            self.qdrant
+                .delete_collection(name)
+                .await
+                .map_err(|err| format!("Error deleting collection '{}': {}", name, err))?;
+        }
+        self.qdrant
+            .create_alias(target, name)
+            .await
+            .map_err(|err| format!("Error pointing alias '{}' at '{}': {}", name, target, err))?;
+        if let Some(live) = live.filter(|live| *live != name) {
+            self.drop_collection(live).await?;
+        }
+
+        let record = CollectionRecord {
+            model: self.generator.model_id.clone(),
+            dimension: self.generator.embedding_dim_len,
+        };
+        registry::record(&self.qdrant, name, &record)
+            .await
+            .map_err(|err| format!("Error updating registry for '{}': {}", name, err))?;
+        self.refresh_known_collections().await;
+
+        Ok(())
+    }
+
+    /// Creates `shadow` with the config registered for `name`
+    /// and re-embeds every item of `live` into it.
+    async fn copy_collection<T>(&self, name: &str, live: &str, shadow: &str) -> Result<(), String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let config = self
+            .collection_configs
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .unwrap_or_default();
+        let record = CollectionRecord {
+            model: self.generator.model_id.clone(),
+            dimension: self.generator.embedding_dim_len,
+        };
+        // Recorded first, so that a failed copy never leaves a collection without a record
+        registry::record(&self.qdrant, shadow, &record)
+            .await
+            .map_err(|err| format!("Error updating registry for '{}': {}", shadow, err))?;
+        self.qdrant
+            .create_collection(shadow, self.generator.embedding_dim_len, &config)
+            .await
+            .map_err(|err| format!("Error creating collection '{}': {}", shadow, err))?;
+        for (field, field_type) in T::payload_indexes() {
+            self.qdrant
+                .with_collection(shadow)
+                .create_field_index(&field, field_type)
+                .await
+                .map_err(|err| format!("Error creating index on '{}': {}", field, err))?;
+        }
+
+        // Read page by page rather than through `scroll`, so that whole pages are embedded and
+        // upserted at once. The old collection is deleted afterwards, so every item has to
+        // make it across
+        let mut offset = None;
+        loop {
+            let (payloads, next) = self
+                .qdrant
+                .with_collection(live)
+                .scroll(None, MIGRATION_BATCH_SIZE, offset)
+                .await
+                .map_err(|err| format!("Error reading items of '{}': {}", live, err))?;
+            let items = payloads
+                .iter()
+                .map(|payload| {
+                    T::from_qdrant_payload(payload).map_err(|err| {
+                        format!(
+                            "An item in '{}' can't be decoded, so migrating would lose it: {}",
+                            live, err
+                        )
+                    })
+                })
+                .collect::<Result<Vec<T>, String>>()?;
+
+            if !items.is_empty() {
+                let embeddings = self.generate_embeddings(&items)?;
+                self.qdrant
+                    .with_collection(shadow)
+                    .insert_batch(
+                        zip(items, embeddings)
+                            .map(|(item, embeddings)| {
+                                let id = item.get_id();
+                                let payload: Payload = item.into();
+                                (embeddings, payload, id)
+                            })
+                            .collect(),
+                    )
+                    .await
+                    .map_err(|err| format!("Error copying items into '{}': {}", shadow, err))?;
+            }
+
+            match next {
+                Some(next) => offset = Some(next),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Deletes the collection, if it exists, and its registry record.
+    async fn drop_collection(&self, name: &str) -> Result<(), String> {
+        let exists = self
+            .qdrant
+            .collection_exists(name)
+            .await
+            .map_err(|err| format!("Error checking collection '{}': {}", name, err))?;
+        if exists {
+            self.qdrant
+                .delete_collection(name)
+                .await
+                .map_err(|err| format!("Error deleting collection '{}': {}", name, err))?;
+        }
+        registry::forget(&self.qdrant, name)
+            .await
+            .map_err(|err| format!("Error updating registry for '{}': {}", name, err))
+    }
+
+    /// Deletes `name` if a failed migration or restore left it behind. Refuses to if an alias
+    /// points at it, since then it is a live collection rather than a leftover.
+    async fn drop_leftover(&self, name: &str) -> Result<(), String> {
+        let aliases = self
+            .qdrant
+            .list_aliases()
+            .await
+            .map_err(|err| format!("Error listing aliases: {}", err))?;
+        if let Some((alias, _)) = aliases.iter().find(|(_, target)| target == name) {
+            return Err(format!(
+                "Collection '{}' is in use by alias '{}', refusing to replace it",
+                name, alias
+            ));
+        }
+        self.drop_collection(name).await
+    }
+
+    /// Snapshots the collection that `name` currently refers to.
+    pub async fn create_snapshot(&self, name: &str) -> Result<SnapshotDescription, String> {
+        let collection = self.resolve_collection(name).await?;
+        self.qdrant
+            .create_snapshot(&collection)
+            .await
+            .map_err(|err| format!("Error creating snapshot of '{}': {}", collection, err))
+    }
+
+    pub async fn list_snapshots(&self, name: &str) -> Result<Vec<SnapshotDescription>, String> {
+        let collection = self.resolve_collection(name).await?;
+        self.qdrant
+            .list_snapshots(&collection)
+            .await
+            .map_err(|err| format!("Error listing snapshots of '{}': {}", collection, err))
+    }
+
+    pub async fn download_snapshot(
+        &self,
+        name: &str,
+        snapshot_name: &str,
+        out_path: &Path,
+    ) -> Result<(), String> {
+        let collection = self.resolve_collection(name).await?;
+        self.qdrant
+            .download_snapshot(&collection, snapshot_name, out_path)
+            .await
+            .map_err(|err| format!("Error downloading snapshot '{}': {}", snapshot_name, err))
+    }
+
+    /// Restores `name` from the snapshot at `location` into a new versioned collection,
+    /// then swaps the `name` alias over to it the same way `migrate_collection` does. A snapshot
+    /// whose dimension doesn't match the current embedding model is rejected before the swap,
+    /// leaving the live collection untouched. Snapshots don't record the model their vectors
+    /// came from, so only the dimension can be checked and the restored collection is recorded
+    /// under the current model. Returns the name of the new collection.
+    pub async fn restore_snapshot(
+        &self,
+        name: &str,
+        location: &str,
+    ) -> Result<CollectionName, String> {
+        self.validate_collection_name(name)?;
+        let live = self.find_collection(name).await?;
+        let target = match &live {
+            Some(live) => next_version(name, live),
+            None => first_version(name),
+        };
+
+        // Left behind by a restore that was interrupted before it could clean up
+        self.drop_leftover(&target).await?;
+
+        if let Err(err) = self.restore_into(&target, location).await {
+            if let Err(cleanup_err) = self.drop_collection(&target).await {
+                return Err(format!("{}\nCleaning up also failed: {}", err, cleanup_err));
+            }
+            return Err(err);
+        }
+
+        self.swap_alias(name, live.as_deref(), &target).await?;
+        Ok(target)
+    }
+
+    /// Restores the snapshot at `location` into `target` and checks its vector dimension
+    async fn restore_into(&self, target: &str, location: &str) -> Result<(), String> {
+        // Snapshots don't carry registry records. Recorded first so that a failed restore
+        // never leaves a collection without a record. This makes the model check in
+        // `verify_collection` trivially pass, so only the dimension is really verified
+        let record = CollectionRecord {
+            model: self.generator.model_id.clone(),
+            dimension: self.generator.embedding_dim_len,
+        };
+        registry::record(&self.qdrant, target, &record)
+            .await
+            .map_err(|err| format!("Error updating registry for '{}': {}", target, err))?;
+
+        self.qdrant
+            .restore_snapshot(target, location)
+            .await
+            .map_err(|err| format!("Error restoring '{}' from '{}': {}", target, location, err))?;
+        self.verify_collection(target).await
+    }
+
+    /// Writes every point of the collection to `path` as JSON Lines, one `ExportedPoint`
+    /// per line. Fails on a point with a UUID id or named vectors, since `ExportedPoint` can't
+    /// hold it. Returns the number of points written.
+    pub async fn export_collection(&self, name: &str, path: &Path) -> Result<u64, String> {
+        let file = File::create(path)
+            .await
+            .map_err(|err| format!("Error creating '{}': {}", path.display(), err))?;
+        let mut writer = BufWriter::new(file);
+
+        let mut exported = 0;
+        let mut offset = None;
+        loop {
+            let (points, next) = self
+                .qdrant
+                .with_collection(name)
+                .scroll_with_vectors(None, EXPORT_BATCH_SIZE, offset)
+                .await
+                .map_err(|err| format!("Error reading points of '{}': {}", name, err))?;
+
+            for (id, vector, payload) in points {
+                let point = ExportedPoint {
+                    id,
+                    vector,
+                    payload: payload
+                        .into_iter()
+                        .map(|(key, value)| (key, value.into_json()))
+                        .collect(),
+                };
+                let mut line = serde_json::to_vec(&point)
+                    .map_err(|err| format!("Error serializing point {}: {}", id, err))?;
+                line.push(b'\n');
+                writer
+                    .write_all(&line)
+                    .await
+                    .map_err(|err| format!("Error writing '{}': {}", path.display(), err))?;
+                exported += 1;
+            }
+
+            match next {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+
+        writer
+            .flush()
+            .await
+            .map_err(|err| format!("Error writing '{}': {}", path.display(), err))?;
+        Ok(exported)
+    }
+
+    /// Upserts every point of a JSON Lines export into the collection, creating it if needed.
+    /// The vectors are kept as they are, so they must match the current embedding model's
+    /// dimension. Returns the number of points imported.
+    pub async fn import_collection(&self, name: &str, path: &Path) -> Result<u64, String> {
+        self.validate_collection_name(name)?;
+        self.ensure_collection_exists(name, &[])
+            .await
+            .map_err(|err| format!("Error when ensuring that collection exists: {}", err))?;
+
+        let file = File::open(path)
+            .await
+            .map_err(|err| format!("Error opening '{}': {}", path.display(), err))?;
+        let mut lines = BufReader::new(file).lines();
+
+        let mut imported = 0;
+        let mut line_number = 0;
+        let mut batch = Vec::new();
+        loop {
+            let line = lines
+                .next_line()
+                .await
+                .map_err(|err| format!("Error reading '{}': {}", path.display(), err))?;
+            let done = line.is_none();
+            line_number += 1;
+
+            if let Some(line) = line.filter(|line| !line.trim().is_empty()) {
+                let point: ExportedPoint = serde_json::from_str(&line)
+                    .map_err(|err| format!("Invalid point on line {}: {}", line_number, err))?;
+                if point.vector.len() as u64 != self.generator.embedding_dim_len {
+                    return Err(format!(
+                        "Point {} on line {} has a {}-dim vector, but the embedding model '{}' produces {}-dim vectors",
+                        point.id,
+                        line_number,
+                        point.vector.len(),
+                        self.generator.model_id,
+                        self.generator.embedding_dim_len
+                    ));
+                }
+                batch.push((point.vector, Payload::from(point.payload), point.id));
+            }
+
+            if batch.len() >= EXPORT_BATCH_SIZE as usize || (done && !batch.is_empty()) {
+                imported += batch.len() as u64;
+                self.qdrant
+                    .with_collection(name)
+                    .insert_batch(std::mem::take(&mut batch))
+                    .await
+                    .map_err(|err| format!("Error importing points into '{}': {}", name, err))?;
+            }
+            if done {
+                break;
+            }
+        }
+
+        Ok(imported)
     }
 
     pub async fn health_check(&self)->Result<HealthCheckReply,QdrantError>{
         self.qdrant.health_check().await
     }
 
+    /// Collections may have been deleted or replaced, so they are looked up, verified
+    /// and their indexes checked again the next time they are written to.
     async fn refresh_known_collections(&self) {
-        let mut known_collections = self.known_collections.write().await;
-        *known_collections = self.qdrant.list_collections().await;
+        self.known_collections.write().await.clear();
+        self.known_indexes.write().await.clear();
+    }
+}
+
+/// `name_v1` for a collection that isn't versioned yet, otherwise the next version after `live`
+fn first_version(name: &str) -> CollectionName {
+    format!("{}{}1", name, VERSION_SEPARATOR)
+}
+
+fn next_version(name: &str, live: &str) -> CollectionName {
+    let version = live
+        .strip_prefix(name)
+        .and_then(|suffix| suffix.strip_prefix(VERSION_SEPARATOR))
+        .and_then(|version| version.parse::<u32>().ok())
+        .unwrap_or(0);
+    format!("{}{}{}", name, VERSION_SEPARATOR, version + 1)
+}
+
+fn operation_status(response: &PointsOperationResponse) -> UpdateStatus {
+    response
+        .result
+        .as_ref()
+        .and_then(|result| UpdateStatus::try_from(result.status).ok())
+        .unwrap_or_default()
+}
+
+fn into_points_selector(selector: PointSelector) -> PointsSelectorOneOf {
+    let ids: Vec<PointId> = match selector {
+        PointSelector::Filter(filter) => return filter.into(),
+        PointSelector::Ids(ids) => ids.into_iter().map(|x| x.into()).collect(),
+        // uses the same hash function that the macro uses
+        PointSelector::StrIds(ids) => ids.iter().map(|x| string_to_u64(x).into()).collect(),
+    };
+    PointsSelectorOneOf::Points(PointsIdsList { ids })
+}
+
+fn into_selector(selector: PointSelector) -> PointsSelector {
+    PointsSelector {
+        points_selector_one_of: Some(into_points_selector(selector)),
     }
 }
@@ -1,11 +1,15 @@
 use super::db_manager::FileVectorDbManager;
-use crate::indexer_api::traits::indexable::{Indexable, IntoPayload};
+use crate::indexer_api::{
+    models::consistency_models::WriteOptions,
+    traits::indexable::{Indexable, IntoPayload},
+};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
 pub async fn index_worker<T>(
     db_manager: Arc<FileVectorDbManager>,
     batch_size: usize,
+    write_options: Option<WriteOptions>,
     mut receiver: mpsc::Receiver<T>,
 ) where
     T: Indexable + IntoPayload,
@@ -16,23 +20,26 @@ pub async fn index_worker<T>(
         queue.push(file);
         if queue.len() >= batch_size {
             let db_manager_clone = Arc::clone(&db_manager);
-            dispatch_queue(db_manager_clone, &mut queue).await;
+            dispatch_queue(db_manager_clone, &mut queue, write_options).await;
         }
     }
     println!("vector index worker receiving channel has been closed");
     if !queue.is_empty() {
-        dispatch_queue(db_manager, &mut queue).await;
+        dispatch_queue(db_manager, &mut queue, write_options).await;
     }
 }
 
-async fn dispatch_queue<T>(db_manager: Arc<FileVectorDbManager>, queue: &mut Vec<T>)
-where
+async fn dispatch_queue<T>(
+    db_manager: Arc<FileVectorDbManager>,
+    queue: &mut Vec<T>,
+    write_options: Option<WriteOptions>,
+) where
     T: Indexable + IntoPayload,
 {
     // println!("dispatching vector queue");
     let mut dispatch: Vec<T> = Vec::new();
     dispatch.append(queue);
-    if let Err(err) = db_manager.insert_many(dispatch).await {
+    if let Err(err) = db_manager.insert_many(dispatch, write_options).await {
         println!("Error inserting files into vector db: {}", err);
     }
 }
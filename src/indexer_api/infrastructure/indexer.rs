@@ -1,4 +1,6 @@
-use tokio::sync::mpsc::Sender;
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use tokio::sync::mpsc::{error::TrySendError, Sender};
+use tokio_util::sync::PollSender;
 
 use crate::{indexer_api::traits::indexable::IntoPayload, Indexable};
 
@@ -25,4 +27,110 @@ where
             }
         }
     }
+
+    /// Sends the items to the worker as the stream yields them, waiting whenever
+    /// the buffer is full. Fails if the worker has stopped.
+    pub async fn index_stream<S>(&self, items: S) -> Result<(), String>
+    where
+        S: Stream<Item = T>,
+    {
+        items.map(Ok).forward(self.sink()).await
+    }
+
+    /// Sends the item without waiting. If the buffer is full or the worker has stopped,
+    /// the item is handed back in the error.
+    pub fn try_index(&self, item: T) -> Result<(), TrySendError<T>> {
+        self.sender.try_send(item)
+    }
+
+    /// A `Sink` feeding the worker, which is not ready while the buffer is full.
+    pub fn sink(&self) -> impl Sink<T, Error = String> + Unpin {
+        PollSender::new(self.sender.clone())
+            .sink_map_err(|err| format!("Indexer: Error sending item: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures_util::stream;
+    use qdrant_client::{qdrant::Value, Payload};
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    struct Item(u64);
+
+    impl Indexable for Item {
+        fn as_map(&self) -> HashMap<String, Value> {
+            HashMap::from([("id".to_string(), (self.0 as i64).into())])
+        }
+
+        fn get_id(&self) -> u64 {
+            self.0
+        }
+
+        fn collection(&self) -> String {
+            "items".to_string()
+        }
+
+        fn embed_label(&self) -> &str {
+            "item"
+        }
+
+        fn from_qdrant_payload(_payload: &HashMap<String, Value>) -> Result<Self, String> {
+            Err("not needed".to_string())
+        }
+    }
+
+    impl From<Item> for Payload {
+        fn from(item: Item) -> Self {
+            Payload::from(item.as_map())
+        }
+    }
+
+    #[tokio::test]
+    async fn try_index_hands_the_item_back_when_the_buffer_is_full() {
+        let (sender, _receiver) = mpsc::channel(1);
+        let indexer = Indexer::new(sender);
+
+        assert!(indexer.try_index(Item(1)).is_ok());
+        match indexer.try_index(Item(2)) {
+            Err(TrySendError::Full(item)) => assert_eq!(item.get_id(), 2),
+            _ => panic!("expected the buffer to be full"),
+        }
+    }
+
+    #[tokio::test]
+    async fn index_stream_delivers_items_in_order() {
+        let (sender, mut receiver) = mpsc::channel::<Item>(1);
+        let indexer = Indexer::new(sender);
+
+        let items = stream::iter((1..=5).map(Item));
+        let receive = async {
+            let mut ids = Vec::new();
+            while ids.len() < 5 {
+                ids.push(receiver.recv().await.unwrap().get_id());
+            }
+            ids
+        };
+
+        let (sent, ids) = tokio::join!(indexer.index_stream(items), receive);
+        assert!(sent.is_ok());
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn fails_once_the_worker_has_stopped() {
+        let (sender, receiver) = mpsc::channel(1);
+        let indexer = Indexer::new(sender);
+        drop(receiver);
+
+        assert!(matches!(
+            indexer.try_index(Item(1)),
+            Err(TrySendError::Closed(_))
+        ));
+        assert!(indexer.index_stream(stream::iter([Item(2)])).await.is_err());
+    }
 }
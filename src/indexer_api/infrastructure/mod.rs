@@ -1,3 +1,4 @@
 pub mod db_manager;
 pub mod index_worker;
-pub mod indexer;
\ No newline at end of file
+pub mod indexer;
+pub mod registry;
\ No newline at end of file
@@ -0,0 +1,112 @@
+use qdrant_client::{
+    qdrant::{Distance, Value},
+    Payload, QdrantError,
+};
+
+use crate::{
+    indexer_api::{
+        models::collection_models::{CollectionConfig, QuantizationType},
+        util::hashing::string_to_u64,
+    },
+    vector_db::db::api::{is_already_exists, QdrantApi},
+};
+
+/// Sidecar collection that records which embedding model each collection was created with.
+/// Every collection gets one point, keyed by the hash of its name.
+pub const REGISTRY_COLLECTION: &str = "vevtor_registry";
+
+const PAGE_SIZE: u32 = 256;
+
+/// What a collection was created with
+pub struct CollectionRecord {
+    pub model: String,
+    pub dimension: u64,
+}
+
+pub async fn record(
+    qdrant: &QdrantApi,
+    collection: &str,
+    record: &CollectionRecord,
+) -> Result<(), QdrantError> {
+    if !qdrant.collection_exists(REGISTRY_COLLECTION).await? {
+        // The registry only stores payloads, so a single dimension is enough for the vectors
+        let config = CollectionConfig {
+            distance: Distance::Dot,
+            quantization: QuantizationType::None,
+            ..Default::default()
+        };
+        match qdrant
+            .create_collection(REGISTRY_COLLECTION, 1, &config)
+            .await
+        {
+            Err(err) if !is_already_exists(&err) => return Err(err),
+            _ => (),
+        }
+    }
+
+    let payload = Payload::from([
+        ("collection", Value::from(collection)),
+        ("model", Value::from(record.model.as_str())),
+        ("dimension", Value::from(record.dimension as i64)),
+    ]);
+    qdrant
+        .with_collection(REGISTRY_COLLECTION)
+        .insert(vec![1.0], payload, string_to_u64(collection))
+        .await?;
+
+    Ok(())
+}
+
+/// The record of a collection, or `None` if it was created without one.
+pub async fn lookup(
+    qdrant: &QdrantApi,
+    collection: &str,
+) -> Result<Option<CollectionRecord>, QdrantError> {
+    if !qdrant.collection_exists(REGISTRY_COLLECTION).await? {
+        return Ok(None);
+    }
+
+    let points = qdrant
+        .with_collection(REGISTRY_COLLECTION)
+        .get_many(vec![string_to_u64(collection)])
+        .await?;
+
+    Ok(points.into_iter().next().and_then(|(_, payload)| {
+        Some(CollectionRecord {
+            model: payload.get("model")?.as_str()?.to_string(),
+            dimension: payload.get("dimension")?.as_integer()? as u64,
+        })
+    }))
+}
+
+/// The names of every collection that has a record.
+pub async fn recorded_collections(qdrant: &QdrantApi) -> Result<Vec<String>, QdrantError> {
+    if !qdrant.collection_exists(REGISTRY_COLLECTION).await? {
+        return Ok(Vec::new());
+    }
+
+    let registry = qdrant.with_collection(REGISTRY_COLLECTION);
+    let mut collections = Vec::new();
+    let mut offset = None;
+    loop {
+        let (payloads, next) = registry.scroll(None, PAGE_SIZE, offset).await?;
+        collections.extend(
+            payloads
+                .iter()
+                .filter_map(|payload| Some(payload.get("collection")?.as_str()?.to_string())),
+        );
+        match next {
+            Some(next) => offset = Some(next),
+            None => return Ok(collections),
+        }
+    }
+}
+
+/// Removes the record of a collection, e.g. after it was deleted.
+pub async fn forget(qdrant: &QdrantApi, collection: &str) -> Result<(), QdrantError> {
+    qdrant
+        .with_collection(REGISTRY_COLLECTION)
+        .remove(string_to_u64(collection))
+        .await
+        .map(|_| ())
+}
@@ -0,0 +1,51 @@
+use qdrant_client::qdrant::{CollectionStatus, CompressionRatio, Distance};
+
+pub struct CollectionInfoModel {
+    pub points_count: u64,
+    pub indexed_vectors_count: u64,
+    pub status: CollectionStatus,
+    /// `None` if the collection doesn't use a single unnamed vector
+    pub vector_size: Option<u64>,
+    pub distance: Option<Distance>,
+}
+
+/// How the vectors of a collection are compressed
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuantizationType {
+    None,
+    Scalar,
+    Product(CompressionRatio),
+    Binary {
+        /// Keep the quantized vectors in RAM even when the originals are on disk
+        always_ram: bool,
+    },
+}
+
+/// Settings used when creating a collection. Fields left as `None` use Qdrant's defaults.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollectionConfig {
+    /// `search_collections` can only merge collections using the same `Cosine` or `Dot` distance
+    pub distance: Distance,
+    pub quantization: QuantizationType,
+    pub hnsw_m: Option<u64>,
+    pub hnsw_ef_construct: Option<u64>,
+    pub on_disk_vectors: Option<bool>,
+    pub on_disk_payload: Option<bool>,
+    pub shard_number: Option<u32>,
+    pub replication_factor: Option<u32>,
+}
+
+impl Default for CollectionConfig {
+    fn default() -> Self {
+        Self {
+            distance: Distance::Cosine,
+            quantization: QuantizationType::Scalar,
+            hnsw_m: None,
+            hnsw_ef_construct: None,
+            on_disk_vectors: None,
+            on_disk_payload: None,
+            shard_number: None,
+            replication_factor: None,
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use qdrant_client::qdrant::WriteOrderingType;
+
+/// How writes are acknowledged and ordered. The default matches Qdrant's:
+/// return as soon as the write is received, with weak ordering
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WriteOptions {
+    /// Return only once the write is applied, so searches made right after it see the new data
+    pub wait: bool,
+    /// How writes are ordered across replicas
+    pub ordering: WriteOrderingType,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            wait: false,
+            ordering: WriteOrderingType::Weak,
+        }
+    }
+}
@@ -1 +1,4 @@
+pub mod collection_models;
+pub mod consistency_models;
+pub mod point_models;
 pub mod search_query_models;
\ No newline at end of file
@@ -0,0 +1,32 @@
+use qdrant_client::{qdrant::Filter, Payload};
+use serde::{Deserialize, Serialize};
+
+/// The points that an operation applies to
+pub enum PointSelector {
+    Ids(Vec<u64>),
+    /// String ids, hashed the same way the `Indexable` derive hashes them
+    StrIds(Vec<String>),
+    Filter(Filter),
+}
+
+/// An operation of a batch applied with `VevtorService::apply_batch`. Operations are
+/// applied in order but not atomically
+pub enum PointOperation<T> {
+    /// Embeds and upserts the items
+    Upsert(Vec<T>),
+    Delete(PointSelector),
+    /// Sets the given payload fields, leaving the others untouched
+    SetPayload(PointSelector, Payload),
+    /// Replaces the whole payload
+    OverwritePayload(PointSelector, Payload),
+    DeletePayloadKeys(PointSelector, Vec<String>),
+}
+
+/// One line of a JSON Lines export: a point with its vector and payload,
+/// independent of the Qdrant version it was read from
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportedPoint {
+    pub id: u64,
+    pub vector: Vec<f32>,
+    pub payload: serde_json::Map<String, serde_json::Value>,
+}
@@ -1,4 +1,9 @@
 pub struct VectorQueryModel{
     pub collection:String,
     pub query:String,
-}
\ No newline at end of file
+}
+
+pub struct MultiCollectionQueryModel {
+    pub collections: Vec<String>,
+    pub query: String,
+}
@@ -1,24 +1,85 @@
-use qdrant_client::qdrant::HealthCheckReply;
+use futures_util::Stream;
+use qdrant_client::{
+    qdrant::{Filter, HealthCheckReply, ReadConsistencyType, SnapshotDescription, UpdateStatus},
+    Payload,
+};
 
 pub use super::infrastructure::indexer::Indexer;
 use super::{
     infrastructure::{db_manager::FileVectorDbManager, index_worker},
-    models::search_query_models::VectorQueryModel,
-    traits::indexable::{Indexable, IntoPayload},
-    util::hashing::string_to_u64,
+    models::{
+        collection_models::{CollectionConfig, CollectionInfoModel},
+        consistency_models::WriteOptions,
+        point_models::{PointOperation, PointSelector},
+        search_query_models::{MultiCollectionQueryModel, VectorQueryModel},
+    },
+    traits::{
+        indexable::{Indexable, IntoPayload},
+        reranker::Reranker,
+    },
+    util::{hashing::string_to_u64, naming::validate_namespace},
 };
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 type Collection = String;
 type ID = u64;
+
+/// How many candidates MMR search fetches for every result it returns
+const MMR_CANDIDATES_PER_RESULT: u64 = 4;
+
 pub struct VevtorService {
     db_manager: Arc<FileVectorDbManager>,
+    reranker: Option<Arc<dyn Reranker>>,
 }
 
 impl VevtorService {
     pub fn new(qdrant_url: &str) -> Self {
-        let db_manager = Arc::new(FileVectorDbManager::new(qdrant_url));
-        Self { db_manager }
+        let db_manager = Arc::new(FileVectorDbManager::new(qdrant_url, None));
+        Self {
+            db_manager,
+            reranker: None,
+        }
+    }
+
+    /**
+    Creates a service whose collections are all prefixed with `namespace` in Qdrant,
+    so several apps can share one Qdrant instance. Collection names are passed in and returned
+    without the prefix, and collections outside of the namespace are never listed or deleted
+    */
+    pub fn with_namespace(qdrant_url: &str, namespace: &str) -> Result<Self, String> {
+        validate_namespace(namespace)?;
+        let db_manager = Arc::new(FileVectorDbManager::new(qdrant_url, Some(namespace)));
+        Ok(Self {
+            db_manager,
+            reranker: None,
+        })
+    }
+
+    /**
+    Registers the re-ranker used by `search_reranked`
+    */
+    pub fn with_reranker<R>(mut self, reranker: R) -> Self
+    where
+        R: Reranker,
+    {
+        self.reranker = Some(Arc::new(reranker));
+        self
+    }
+
+    /**
+    Sets `wait` and the write ordering of every write that doesn't specify its own.
+    Use `wait: true` when searches made right after indexing must see the new items
+    */
+    pub fn set_write_options(&self, write_options: WriteOptions) {
+        self.db_manager.set_write_options(write_options)
+    }
+
+    /**
+    Sets how many replicas every read that doesn't specify its own consistency must be answered by.
+    `None` leaves it to Qdrant. Only matters when collections are replicated
+    */
+    pub fn set_read_consistency(&self, read_consistency: Option<ReadConsistencyType>) {
+        self.db_manager.set_read_consistency(read_consistency)
     }
 
     pub async fn search<T>(
@@ -30,48 +91,649 @@ impl VevtorService {
         T: Indexable + IntoPayload,
     {
         self.db_manager
-            .search::<T>(&params.query, &params.collection, top_k)
+            .search::<T>(&params.query, &params.collection, top_k, None)
+            .await
+    }
+
+    /**
+    Same as `search`, but with the given read consistency instead of the default one
+    */
+    pub async fn search_with_consistency<T>(
+        &self,
+        params: &VectorQueryModel,
+        top_k: u64,
+        read_consistency: ReadConsistencyType,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .search::<T>(
+                &params.query,
+                &params.collection,
+                top_k,
+                Some(read_consistency),
+            )
+            .await
+    }
+
+    /**
+    Searches with maximal marginal relevance re-ranking, to avoid returning near-duplicates.
+    `lambda` ranges from 0.0 (most diverse) to 1.0 (ranked by cosine similarity to the query only)
+    */
+    pub async fn search_mmr<T>(
+        &self,
+        params: &VectorQueryModel,
+        top_k: u64,
+        lambda: f32,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .search_mmr::<T>(
+                &params.query,
+                &params.collection,
+                top_k,
+                top_k * MMR_CANDIDATES_PER_RESULT,
+                lambda.clamp(0.0, 1.0),
+            )
+            .await
+    }
+
+    /**
+    Retrieves the top `candidates` results and re-scores them with the registered re-ranker,
+    returning the best `top_k`. The scores returned are the re-ranker's scores
+    */
+    pub async fn search_reranked<T>(
+        &self,
+        params: &VectorQueryModel,
+        top_k: u64,
+        candidates: u64,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let reranker = self
+            .reranker
+            .as_deref()
+            .ok_or("No reranker has been registered with `with_reranker`")?;
+
+        self.db_manager
+            .search_reranked::<T>(
+                &params.query,
+                &params.collection,
+                top_k,
+                candidates,
+                reranker,
+            )
+            .await
+    }
+
+    /**
+    Searches and groups the results by the value of the `group_by` payload field,
+    so that many hits from e.g. the same folder only take up one group.
+    Returns up to `groups` groups of up to `group_size` items each
+    */
+    pub async fn search_groups<T>(
+        &self,
+        params: &VectorQueryModel,
+        group_by: &str,
+        group_size: u32,
+        groups: u32,
+    ) -> Result<Vec<(String, Vec<(T, f32)>)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .search_groups::<T>(
+                &params.query,
+                &params.collection,
+                group_by,
+                group_size,
+                groups,
+            )
             .await
     }
 
+    /**
+    Searches with an embedding computed elsewhere, e.g. the average of several documents.
+    Fails if the vector's dimension doesn't match the collection
+    */
+    pub async fn search_by_vector<T>(
+        &self,
+        collection: &str,
+        vector: Vec<f32>,
+        top_k: u64,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .search_by_vector::<T>(vector, collection, top_k)
+            .await
+    }
+
+    /**
+    Runs many searches at once, embedding all of the queries in a single pass.
+    The returned results are in the same order as `params`
+    */
+    pub async fn search_batch<T>(
+        &self,
+        params: &[VectorQueryModel],
+        top_k: u64,
+    ) -> Result<Vec<Vec<(T, f32)>>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        let queries: Vec<(&str, &str)> = params
+            .iter()
+            .map(|params| (params.collection.as_str(), params.query.as_str()))
+            .collect();
+
+        self.db_manager.search_batch::<T>(&queries, top_k).await
+    }
+
+    /**
+    Searches several collections with a single query embedding.
+    Each hit is returned with the name of the collection it came from, ordered by score.
+    The collections must all use the same distance, `Cosine` or `Dot`, for their scores to be
+    comparable; `Euclid` and `Manhattan` collections are rejected
+    */
+    pub async fn search_collections<T>(
+        &self,
+        params: &MultiCollectionQueryModel,
+        top_k: u64,
+    ) -> Result<Vec<(T, f32, Collection)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .search_collections::<T>(&params.query, &params.collections, top_k)
+            .await
+    }
+
+    /**
+    Finds items similar to the `positive` ids and unlike the `negative` ids in a collection,
+    without having to re-send any text
+    */
+    pub async fn recommend<T>(
+        &self,
+        collection: &str,
+        positive: Vec<ID>,
+        negative: Vec<ID>,
+        top_k: u64,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .recommend::<T>(collection, positive, negative, top_k)
+            .await
+    }
+
+    pub async fn recommend_by_str_id<T>(
+        &self,
+        collection: &str,
+        positive: Vec<String>,
+        negative: Vec<String>,
+        top_k: u64,
+    ) -> Result<Vec<(T, f32)>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        // uses the same hash function that the macro uses
+        self.recommend::<T>(
+            collection,
+            positive.iter().map(|x| string_to_u64(x)).collect(),
+            negative.iter().map(|x| string_to_u64(x)).collect(),
+            top_k,
+        )
+        .await
+    }
+
+    /**
+    Iterates over every item in a collection, optionally narrowed down by `filter`.
+    Items are fetched from Qdrant lazily, `page_size` at a time. An item that can't be decoded
+    into `T` is yielded as an error instead of being skipped, so audits see every item
+    */
+    pub fn scroll<'a, T>(
+        &'a self,
+        collection: &'a str,
+        filter: Option<Filter>,
+        page_size: u32,
+    ) -> impl Stream<Item = Result<T, String>> + 'a
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager.scroll::<T>(collection, filter, page_size)
+    }
+
+    /**
+    Lists the logical names of the collections. Collections that were migrated
+    are listed under their alias rather than their versioned name
+    */
     pub async fn list_collections(&self) -> Vec<String> {
         self.db_manager.list_collections().await
     }
 
-    pub async fn delete_all_collections(&self) {
-        self.db_manager.reset_all().await;
+    /**
+    Returns the versioned collection that a logical collection name currently points to
+    */
+    pub async fn resolve_collection(&self, name: &str) -> Result<String, String> {
+        self.db_manager.resolve_collection(name).await
+    }
+
+    /**
+    Re-embeds every item in a collection with the current embedding model without taking search down.
+    The items are copied into a new versioned collection, then the `name` alias is swapped over to it,
+    so every other call on `name` keeps working throughout. Returns the name of the new collection.
+    Collections created by an older version of vevtor are plain collections rather than aliases:
+    those are unavailable for a moment during their first migration, while the alias replaces them
+    */
+    pub async fn migrate_collection<T>(&self, name: &str) -> Result<String, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager.migrate_collection::<T>(name).await
+    }
+
+    /**
+    Counts the items in a collection, optionally only those matching `filter`.
+    Pass `exact: false` for a faster, approximate count
+    */
+    pub async fn count(
+        &self,
+        collection: &str,
+        filter: Option<Filter>,
+        exact: bool,
+    ) -> Result<u64, String> {
+        self.db_manager
+            .count(collection, filter, exact)
+            .await
+            .map_err(|err| format!("Error counting items: {}", err))
+    }
+
+    /**
+    Reports the point counts, status and vector config of a collection
+    */
+    pub async fn collection_info(&self, name: &str) -> Result<CollectionInfoModel, String> {
+        self.db_manager.collection_info(name).await
+    }
+
+    /**
+    Deletes every collection that vevtor created, returning the outcome for each one.
+    With a namespace that is every collection in it, otherwise every collection that was
+    created through this crate. Collections created by anything else are never touched.
+    If any collection fails to delete, the registry of vevtor's collections is kept,
+    so that calling this again retries the ones that are left
+    */
+    pub async fn delete_all_collections(
+        &self,
+    ) -> Result<Vec<(Collection, Result<(), String>)>, String> {
+        let results = self.db_manager.reset_owned().await?;
+        Ok(results
+            .into_iter()
+            .map(|(collection, result)| {
+                let result = result.map_err(|err| format!("Error deleting collection: {}", err));
+                (collection, result)
+            })
+            .collect())
+    }
+
+    /**
+    Lists the collections that `delete_all_collections` would delete, without deleting anything
+    */
+    pub async fn delete_all_collections_dry_run(&self) -> Result<Vec<Collection>, String> {
+        self.db_manager.owned_collections().await
+    }
+
+    /**
+    Writes every item of a collection to `path` as JSON Lines, one `ExportedPoint` with its id,
+    vector and payload per line. Unlike snapshots, exports don't depend on the Qdrant version
+    and can be read by any tool. Points not written by vevtor, with a UUID id or named vectors,
+    can't be exported: the export fails on them rather than leaving them out.
+    Returns the number of items exported
+    */
+    pub async fn export_collection(
+        &self,
+        collection: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, String> {
+        self.db_manager
+            .export_collection(collection, path.as_ref())
+            .await
+    }
+
+    /**
+    Upserts every item of a JSON Lines file written by `export_collection` into a collection,
+    creating it if needed. The stored vectors are reused rather than re-embedded, so they must
+    come from the embedding model in use. Returns the number of items imported
+    */
+    pub async fn import_collection(
+        &self,
+        collection: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<u64, String> {
+        self.db_manager
+            .import_collection(collection, path.as_ref())
+            .await
+    }
+
+    /**
+    Takes a snapshot of a collection on the Qdrant server. Logical names are resolved
+    through their alias, so the snapshot is of the collection currently being searched
+    */
+    pub async fn create_snapshot(&self, collection: &str) -> Result<SnapshotDescription, String> {
+        self.db_manager.create_snapshot(collection).await
+    }
+
+    pub async fn list_snapshots(
+        &self,
+        collection: &str,
+    ) -> Result<Vec<SnapshotDescription>, String> {
+        self.db_manager.list_snapshots(collection).await
+    }
+
+    /**
+    Downloads a snapshot returned by `create_snapshot` or `list_snapshots` to `out_path`.
+    Snapshots are served over Qdrant's REST API, which is expected on port 6333
+    when the service was created with the default gRPC port 6334
+    */
+    pub async fn download_snapshot(
+        &self,
+        collection: &str,
+        snapshot_name: &str,
+        out_path: impl AsRef<Path>,
+    ) -> Result<(), String> {
+        self.db_manager
+            .download_snapshot(collection, snapshot_name, out_path.as_ref())
+            .await
+    }
+
+    /**
+    Restores a collection from a snapshot, replacing it if it exists. `location` must be readable
+    by the Qdrant server: an `http(s)://` URL or a `file://` path in its snapshots directory.
+    With the container from `docker/`, snapshots downloaded to `docker/snapshots/` can be restored
+    from `file:///qdrant/snapshots/<file>`.
+    The snapshot is restored into a new versioned collection, which only replaces the live one
+    once its vector dimension was checked against the embedding model in use. Snapshots don't
+    record which model produced their vectors, so a snapshot from another model with the same
+    dimension is accepted. Returns the name of the new collection
+    */
+    pub async fn restore_snapshot(
+        &self,
+        collection: &str,
+        location: &str,
+    ) -> Result<String, String> {
+        self.db_manager.restore_snapshot(collection, location).await
     }
 
     pub async fn ensure_collection_exists(&self, name: &str) -> Result<(), String> {
+        self.db_manager.validate_collection_name(name)?;
+        self.db_manager
+            .ensure_collection_exists(name, &[])
+            .await
+            .map_err(|err| format!("Error when ensuring that collection exists: {}", err))
+    }
+
+    /**
+    Same as `ensure_collection_exists`, but also creates the payload field indexes
+    declared on `T` with `#[indexable(payload_index = "...")]`, including on a collection
+    that already exists. Indexing items of type `T` does this too
+    */
+    pub async fn ensure_collection_exists_for<T>(&self, name: &str) -> Result<(), String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager.validate_collection_name(name)?;
         self.db_manager
-            .ensure_collection_exists(name)
+            .ensure_collection_exists(name, &T::payload_indexes())
             .await
             .map_err(|err| format!("Error when ensuring that collection exists: {}", err))
     }
 
-    pub async fn delete_by_str_id(&self, ids: Vec<(Collection, String)>) {
+    /**
+    Same as `ensure_collection_exists`, but creates the collection with `config` if it is missing.
+    The config is also remembered for whenever the indexer needs to create the collection
+    */
+    pub async fn ensure_collection_exists_with(
+        &self,
+        name: &str,
+        config: CollectionConfig,
+    ) -> Result<(), String> {
+        self.set_collection_config(name, config).await;
+        self.ensure_collection_exists(name).await
+    }
+
+    /**
+    Checks that every collection vevtor created (see `delete_all_collections`) was created for
+    the embedding model in use. Call this at startup to find every mismatch at once after
+    switching models; indexing into a mismatched collection fails on its own as well
+    */
+    pub async fn verify_collections(&self) -> Result<(), String> {
+        self.db_manager.verify_collections().await
+    }
+
+    /**
+    Overrides the distance metric, HNSW, on-disk, quantization and sharding settings
+    used when this collection is created. Has no effect on collections that already exist
+    */
+    pub async fn set_collection_config(&self, name: &str, config: CollectionConfig) {
+        self.db_manager.set_collection_config(name, config).await
+    }
+
+    pub async fn delete_by_str_id(
+        &self,
+        ids: Vec<(Collection, String)>,
+    ) -> Vec<(Collection, Result<UpdateStatus, String>)> {
         // uses the same hash function that the macro uses
+        self.delete_by_id(
+            ids.into_iter()
+                .map(|(x, y)| (x, string_to_u64(&y)))
+                .collect(),
+        )
+        .await
+    }
+
+    /**
+    Deletes items by id, returning the outcome for every collection that was touched
+    */
+    pub async fn delete_by_id(
+        &self,
+        ids: Vec<(Collection, ID)>,
+    ) -> Vec<(Collection, Result<UpdateStatus, String>)> {
         self.db_manager
-            .delete_many(
-                ids.into_iter()
-                    .map(|(x, y)| (x, string_to_u64(&y)))
-                    .collect(),
-            )
+            .delete_many(ids)
             .await
+            .into_iter()
+            .map(|(collection, result)| {
+                let result = result.map_err(|err| format!("Error deleting items: {}", err));
+                (collection, result)
+            })
+            .collect()
     }
 
-    pub async fn delete_by_id(&self, ids: Vec<(Collection, ID)>) {
-        self.db_manager.delete_many(ids).await
+    /**
+    Looks up a single item by id. Useful for checking whether an item has already been indexed
+    */
+    pub async fn get_by_id<T>(&self, collection: &str, id: ID) -> Result<Option<T>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.get_many_by_id::<T>(collection, vec![id])
+            .await
+            .map(|mut items| items.pop().flatten())
+    }
+
+    pub async fn get_by_str_id<T>(&self, collection: &str, id: &str) -> Result<Option<T>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        // uses the same hash function that the macro uses
+        self.get_by_id::<T>(collection, string_to_u64(id)).await
+    }
+
+    /**
+    Looks up many items by id at once. The result is in the same order as `ids`,
+    with `None` for every id that isn't in the collection
+    */
+    pub async fn get_many_by_id<T>(
+        &self,
+        collection: &str,
+        ids: Vec<ID>,
+    ) -> Result<Vec<Option<T>>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager.get_many::<T>(collection, ids).await
+    }
+
+    pub async fn get_many_by_str_id<T>(
+        &self,
+        collection: &str,
+        ids: Vec<String>,
+    ) -> Result<Vec<Option<T>>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.get_many_by_id::<T>(collection, ids.iter().map(|x| string_to_u64(x)).collect())
+            .await
+    }
+
+    /**
+    Deletes every item in a collection that matches `filter`,
+    e.g. all items whose path is inside a removed directory
+    */
+    pub async fn delete_by_filter(
+        &self,
+        collection: &str,
+        filter: Filter,
+    ) -> Result<UpdateStatus, String> {
+        self.db_manager
+            .delete_by_filter(collection, filter)
+            .await
+            .map_err(|err| format!("Error deleting items by filter: {}", err))
+    }
+
+    /**
+    Sets the given payload fields on the selected items without re-embedding them.
+    Fields that aren't in `payload` are left untouched
+    */
+    pub async fn update_payload(
+        &self,
+        collection: &str,
+        selector: PointSelector,
+        payload: Payload,
+    ) -> Result<UpdateStatus, String> {
+        self.db_manager
+            .update_payload(collection, selector, payload)
+            .await
+            .map_err(|err| format!("Error updating payload: {}", err))
+    }
+
+    /**
+    Replaces the entire payload of the selected items without re-embedding them.
+    The new payload must still decode into the collection's `Indexable` type
+    */
+    pub async fn overwrite_payload(
+        &self,
+        collection: &str,
+        selector: PointSelector,
+        payload: Payload,
+    ) -> Result<UpdateStatus, String> {
+        self.db_manager
+            .overwrite_payload(collection, selector, payload)
+            .await
+            .map_err(|err| format!("Error overwriting payload: {}", err))
+    }
+
+    pub async fn delete_payload_keys(
+        &self,
+        collection: &str,
+        selector: PointSelector,
+        keys: Vec<String>,
+    ) -> Result<UpdateStatus, String> {
+        self.db_manager
+            .delete_payload_keys(collection, selector, keys)
+            .await
+            .map_err(|err| format!("Error deleting payload keys: {}", err))
+    }
+
+    /**
+    Applies an ordered list of operations to one collection in a single request, e.g. upserting
+    a renamed file under its new id and deleting its old one. Qdrant applies them in order, but
+    not in isolation: a concurrent search can run between two operations, and a failing
+    operation does not roll back the ones before it. Put `Upsert(new)` before `Delete(old)` so
+    that readers see both points at worst, never neither. Upserted items must belong to
+    `collection`. Returns the status of each operation
+    */
+    pub async fn apply_batch<T>(
+        &self,
+        collection: &str,
+        operations: Vec<PointOperation<T>>,
+    ) -> Result<Vec<UpdateStatus>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .apply_batch(collection, operations, None)
+            .await
+    }
+
+    /**
+    Same as `apply_batch`, but with the given write options instead of the default ones
+    */
+    pub async fn apply_batch_with_options<T>(
+        &self,
+        collection: &str,
+        operations: Vec<PointOperation<T>>,
+        write_options: WriteOptions,
+    ) -> Result<Vec<UpdateStatus>, String>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.db_manager
+            .apply_batch(collection, operations, Some(write_options))
+            .await
     }
 
     pub fn spawn_index_worker<T>(&self, batch_size: usize, buffer_size: usize) -> Indexer<T>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.spawn_worker(batch_size, buffer_size, None)
+    }
+
+    /**
+    Same as `spawn_index_worker`, but every upsert made by the worker uses the given
+    write options instead of the default ones
+    */
+    pub fn spawn_index_worker_with_options<T>(
+        &self,
+        batch_size: usize,
+        buffer_size: usize,
+        write_options: WriteOptions,
+    ) -> Indexer<T>
+    where
+        T: Indexable + IntoPayload,
+    {
+        self.spawn_worker(batch_size, buffer_size, Some(write_options))
+    }
+
+    fn spawn_worker<T>(
+        &self,
+        batch_size: usize,
+        buffer_size: usize,
+        write_options: Option<WriteOptions>,
+    ) -> Indexer<T>
     where
         T: Indexable + IntoPayload,
     {
         let db_manager_clone = Arc::clone(&self.db_manager);
         let (sender, receiver) = tokio::sync::mpsc::channel::<T>(buffer_size);
         tokio::spawn(async move {
-            index_worker::index_worker(db_manager_clone, batch_size, receiver).await;
+            index_worker::index_worker(db_manager_clone, batch_size, write_options, receiver).await;
         });
         Indexer::new(sender)
     }
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use qdrant_client::qdrant::Value;
+use qdrant_client::qdrant::{FieldType, Value};
 
 pub trait IntoPayload: Into<qdrant_client::Payload> {}
 
@@ -16,5 +16,12 @@ pub trait Indexable: Send + Sync + Sized + 'static {
 
     fn embed_label(&self) -> &str;
 
+    /// Payload fields that get a Qdrant field index when the collection is created.
+    /// The derive macro fills this in from `#[indexable(payload_index = "...")]` field attributes,
+    /// keyed by the field's `#[serde(rename = "...")]` name if it has one.
+    fn payload_indexes() -> Vec<(String, FieldType)> {
+        Vec::new()
+    }
+
     fn from_qdrant_payload(payload: &HashMap<String, Value>) -> Result<Self, String>;
 }
@@ -1 +1,2 @@
-pub mod indexable;
\ No newline at end of file
+pub mod indexable;
+pub mod reranker;
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub trait Reranker: Send + Sync + 'static {
+    /// Scores every document against the query. Higher is more relevant.
+    /// The returned scores must be in the same order as `documents`.
+    fn rerank(&self, query: &str, documents: Vec<&str>) -> Result<Vec<f32>, String>;
+}
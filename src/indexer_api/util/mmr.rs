@@ -0,0 +1,86 @@
+/// Picks up to `top_k` candidate vectors using maximal marginal relevance.
+///
+/// Relevance and redundancy are both cosine similarities, to `query` and to the already picked
+/// candidates, so they are on the same scale whatever distance metric the collection uses.
+/// `lambda` trades relevance (`1.0`) against diversity (`0.0`).
+/// Returns the indices of the picked candidates, in pick order.
+pub fn mmr_select(query: &[f32], candidates: &[&[f32]], lambda: f32, top_k: usize) -> Vec<usize> {
+    let mut selected: Vec<usize> = Vec::new();
+    let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+
+    while selected.len() < top_k && !remaining.is_empty() {
+        let mut best: Option<(usize, f32)> = None;
+
+        for (position, &index) in remaining.iter().enumerate() {
+            let vector = candidates[index];
+            let relevance = cosine_similarity(query, vector);
+            let redundancy = selected
+                .iter()
+                .map(|&picked| cosine_similarity(vector, candidates[picked]))
+                .fold(0.0_f32, f32::max);
+
+            let score = lambda * relevance - (1.0 - lambda) * redundancy;
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((position, score));
+            }
+        }
+
+        if let Some((position, _)) = best {
+            selected.push(remaining.remove(position));
+        }
+    }
+
+    selected
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: [f32; 2] = [1.0, 0.0];
+    // The two closest candidates are duplicates, the third points elsewhere
+    const CLOSE: [f32; 2] = [1.0, 0.1];
+    const FAR: [f32; 2] = [0.0, 1.0];
+
+    #[test]
+    fn lambda_one_ranks_by_relevance_only() {
+        let candidates: Vec<&[f32]> = vec![&FAR, &CLOSE, &CLOSE];
+        assert_eq!(mmr_select(&QUERY, &candidates, 1.0, 3), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn duplicates_are_pushed_down_by_diversity() {
+        let candidates: Vec<&[f32]> = vec![&CLOSE, &CLOSE, &FAR];
+        assert_eq!(mmr_select(&QUERY, &candidates, 0.3, 2), vec![0, 2]);
+    }
+
+    #[test]
+    fn lambda_zero_picks_the_least_redundant() {
+        let candidates: Vec<&[f32]> = vec![&CLOSE, &CLOSE, &FAR];
+        // Nothing is picked yet, so the first pick is a tie that goes to the first candidate
+        assert_eq!(mmr_select(&QUERY, &candidates, 0.0, 2), vec![0, 2]);
+    }
+
+    #[test]
+    fn picks_at_most_the_number_of_candidates() {
+        let candidates: Vec<&[f32]> = vec![&CLOSE];
+        assert_eq!(mmr_select(&QUERY, &candidates, 0.5, 5), vec![0]);
+        assert!(mmr_select(&QUERY, &[], 0.5, 5).is_empty());
+    }
+
+    #[test]
+    fn zero_vectors_have_no_similarity() {
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &QUERY), 0.0);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod hashing;
+pub mod mmr;
+pub mod naming;
 pub mod vec;
\ No newline at end of file
@@ -0,0 +1,147 @@
+use crate::{
+    indexer_api::infrastructure::registry::REGISTRY_COLLECTION,
+    vector_db::db::api::NAMESPACE_SEPARATOR,
+};
+
+/// Qdrant's limit on the length of a collection name
+const MAX_NAME_LEN: usize = 255;
+
+/// Separates a name from the version of the collections behind its alias, as in `files_v2`
+pub const VERSION_SEPARATOR: &str = "_v";
+
+/// Room left for the `_vN` suffix of the versioned collections behind an alias
+const VERSION_SUFFIX_LEN: usize = 6;
+
+/// Checks that `name` can be used as a collection name, once `namespace` is prepended to it.
+pub fn validate_collection_name(name: &str, namespace: Option<&str>) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Collection name must not be empty".to_string());
+    }
+    if name == REGISTRY_COLLECTION {
+        return Err(format!(
+            "Collection name '{}' is reserved for vevtor's collection registry",
+            name
+        ));
+    }
+    if let Some(c) = name.chars().find(|c| !is_allowed_char(*c)) {
+        return Err(format!(
+            "Collection name '{}' contains '{}'. Only ASCII letters, digits, '_', '-' and '.' are allowed",
+            name, c
+        ));
+    }
+    // Otherwise it could be the versioned collection behind another name's alias
+    if has_version_suffix(name) {
+        return Err(format!(
+            "Collection name '{}' must not end in '{}' followed by digits, which is reserved for versioned collections",
+            name, VERSION_SEPARATOR
+        ));
+    }
+
+    let len = match namespace {
+        Some(namespace) => namespace.len() + NAMESPACE_SEPARATOR.len() + name.len(),
+        None => name.len(),
+    };
+    let max_len = MAX_NAME_LEN - VERSION_SUFFIX_LEN;
+    if len > max_len {
+        return Err(format!(
+            "Collection name '{}' is too long. Including the namespace it is {} characters, the limit is {}",
+            name, len, max_len
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn validate_namespace(namespace: &str) -> Result<(), String> {
+    if namespace.is_empty() {
+        return Err("Namespace must not be empty".to_string());
+    }
+    if let Some(c) = namespace.chars().find(|c| !is_allowed_char(*c)) {
+        return Err(format!(
+            "Namespace '{}' contains '{}'. Only ASCII letters, digits, '_', '-' and '.' are allowed",
+            namespace, c
+        ));
+    }
+    // Otherwise one namespace could be the prefix of another one's collections
+    if namespace.contains(NAMESPACE_SEPARATOR) || namespace.ends_with('_') {
+        return Err(format!(
+            "Namespace '{}' must not contain '{}' or end with '_'",
+            namespace, NAMESPACE_SEPARATOR
+        ));
+    }
+
+    Ok(())
+}
+
+fn has_version_suffix(name: &str) -> bool {
+    name.rsplit_once(VERSION_SEPARATOR)
+        .is_some_and(|(_, version)| {
+            !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+fn is_allowed_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plain_names() {
+        assert!(validate_collection_name("files", None).is_ok());
+        assert!(validate_collection_name("project-1.files", Some("app")).is_ok());
+        assert!(validate_collection_name("files_v2a", None).is_ok());
+        assert!(validate_collection_name("files_v", None).is_ok());
+        assert!(validate_collection_name("files.v2", None).is_ok());
+    }
+
+    #[test]
+    fn rejects_version_suffixes() {
+        assert!(validate_collection_name("files_v1", None).is_err());
+        assert!(validate_collection_name("files_v12", Some("app")).is_err());
+        assert!(validate_collection_name("_v3", None).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_reserved_names() {
+        assert!(validate_collection_name("", None).is_err());
+        assert!(validate_collection_name(REGISTRY_COLLECTION, None).is_err());
+        assert!(validate_collection_name(REGISTRY_COLLECTION, Some("app")).is_err());
+    }
+
+    #[test]
+    fn rejects_disallowed_characters() {
+        assert!(validate_collection_name("my files", None).is_err());
+        assert!(validate_collection_name("a/b", None).is_err());
+        assert!(validate_collection_name("café", None).is_err());
+    }
+
+    #[test]
+    fn length_limit_includes_namespace_and_version_suffix() {
+        let max_len = MAX_NAME_LEN - VERSION_SUFFIX_LEN;
+        assert!(validate_collection_name(&"a".repeat(max_len), None).is_ok());
+        assert!(validate_collection_name(&"a".repeat(max_len + 1), None).is_err());
+
+        let namespace = "app";
+        let name = "a".repeat(max_len - namespace.len() - NAMESPACE_SEPARATOR.len());
+        assert!(validate_collection_name(&name, Some(namespace)).is_ok());
+        assert!(validate_collection_name(&format!("{}a", name), Some(namespace)).is_err());
+    }
+
+    #[test]
+    fn validates_namespaces() {
+        assert!(validate_namespace("app").is_ok());
+        assert!(validate_namespace("my-app.v2").is_ok());
+        assert!(validate_namespace("").is_err());
+        assert!(validate_namespace("my app").is_err());
+    }
+
+    #[test]
+    fn namespaces_cannot_contain_or_end_in_the_separator() {
+        assert!(validate_namespace("a__b").is_err());
+        assert!(validate_namespace("app_").is_err());
+        assert!(validate_namespace("my_app").is_ok());
+    }
+}
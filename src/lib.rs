@@ -2,9 +2,17 @@ mod indexer_api;
 mod vector_db;
 
 pub use indexable_macro::Indexable;
-pub use indexer_api::models::search_query_models::VectorQueryModel;
+pub use indexer_api::models::collection_models::{
+    CollectionConfig, CollectionInfoModel, QuantizationType,
+};
+pub use indexer_api::models::consistency_models::WriteOptions;
+pub use indexer_api::models::point_models::{ExportedPoint, PointOperation, PointSelector};
+pub use indexer_api::models::search_query_models::{MultiCollectionQueryModel, VectorQueryModel};
 pub use indexer_api::service::VevtorService;
 pub use indexer_api::traits::indexable::Indexable;
 pub use indexer_api::service::Indexer;
+pub use indexer_api::traits::reranker::Reranker;
+pub use vector_db::embeddings::reranker::FastembedReranker;
+pub use fastembed::RerankerModel;
 pub use qdrant_client;
 pub use twox_hash;
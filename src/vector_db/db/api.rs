@@ -1,44 +1,135 @@
 use qdrant_client::qdrant::{
-    CollectionOperationResponse, CreateCollectionBuilder, Distance, HealthCheckReply, ScalarQuantizationBuilder, VectorParamsBuilder
+    quantization_config::Quantization, vectors_config::Config, BinaryQuantizationBuilder,
+    CollectionInfo, CollectionOperationResponse, CreateAliasBuilder, CreateCollectionBuilder,
+    HealthCheckReply, HnswConfigDiffBuilder, ProductQuantizationBuilder, ReadConsistencyType,
+    ScalarQuantizationBuilder, SnapshotDescription, SnapshotDownloadBuilder, VectorParams,
+    VectorParamsBuilder,
 };
 use qdrant_client::{Qdrant, QdrantError};
+use std::{path::Path, sync::RwLock};
 
 use super::builders::with_collection::WithCollectionBuilder;
+use crate::indexer_api::models::{
+    collection_models::{CollectionConfig, QuantizationType},
+    consistency_models::WriteOptions,
+};
+
+/// Separates the namespace from the collection name in the names stored in Qdrant
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+/// The ports Qdrant serves gRPC and REST on by default
+const DEFAULT_GRPC_PORT: &str = ":6334";
+const DEFAULT_REST_PORT: &str = ":6333";
 
+/// Collection names passed in and returned are always unqualified. With a namespace,
+/// they are prefixed with it on the way to Qdrant, and collections outside of it are ignored.
+///
+/// Every `WithCollectionBuilder` it hands out starts with the default write options
+/// and read consistency set here.
 pub struct QdrantApi {
     client: Qdrant,
+    namespace: Option<String>,
+    rest_url: String,
+    write_options: RwLock<WriteOptions>,
+    read_consistency: RwLock<Option<ReadConsistencyType>>,
 }
 
 impl QdrantApi {
-    pub fn new(url: &str) -> Self {
+    pub fn new(url: &str, namespace: Option<&str>) -> Self {
         let client = Qdrant::from_url(url)
             .timeout(std::time::Duration::from_secs(30))
             .build()
             .unwrap();
-        Self { client }
+        Self {
+            client,
+            namespace: namespace.map(|namespace| namespace.to_string()),
+            rest_url: rest_url(url),
+            write_options: RwLock::new(WriteOptions::default()),
+            read_consistency: RwLock::new(None),
+        }
+    }
+
+    pub fn set_write_options(&self, write_options: WriteOptions) {
+        *self.write_options.write().unwrap() = write_options;
+    }
+
+    pub fn set_read_consistency(&self, read_consistency: Option<ReadConsistencyType>) {
+        *self.read_consistency.write().unwrap() = read_consistency;
+    }
+
+    /// The name of the collection as stored in Qdrant.
+    pub fn qualify(&self, name: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// The unqualified name, or `None` if the collection is outside of the namespace.
+    fn unqualify(&self, name: String) -> Option<String> {
+        match &self.namespace {
+            Some(namespace) => name
+                .strip_prefix(namespace.as_str())
+                .and_then(|name| name.strip_prefix(NAMESPACE_SEPARATOR))
+                .map(|name| name.to_string()),
+            None => Some(name),
+        }
     }
 
     pub async fn create_collection(
         &self,
         name: &str,
         num_features: u64,
+        config: &CollectionConfig,
     ) -> Result<WithCollectionBuilder, QdrantError> {
+        let mut vectors = VectorParamsBuilder::new(num_features, config.distance);
+        if let Some(on_disk) = config.on_disk_vectors {
+            vectors = vectors.on_disk(on_disk);
+        }
+
+        let mut request = CreateCollectionBuilder::new(self.qualify(name)).vectors_config(vectors);
+        if let Some(quantization) = quantization(&config.quantization) {
+            request = request.quantization_config(quantization);
+        }
+        if config.hnsw_m.is_some() || config.hnsw_ef_construct.is_some() {
+            let mut hnsw = HnswConfigDiffBuilder::default();
+            if let Some(m) = config.hnsw_m {
+                hnsw = hnsw.m(m);
+            }
+            if let Some(ef_construct) = config.hnsw_ef_construct {
+                hnsw = hnsw.ef_construct(ef_construct);
+            }
+            request = request.hnsw_config(hnsw);
+        }
+        if let Some(on_disk_payload) = config.on_disk_payload {
+            request = request.on_disk_payload(on_disk_payload);
+        }
+        if let Some(shard_number) = config.shard_number {
+            request = request.shard_number(shard_number);
+        }
+        if let Some(replication_factor) = config.replication_factor {
+            request = request.replication_factor(replication_factor);
+        }
+
         self.client
-            .create_collection(
-                CreateCollectionBuilder::new(name)
-                    .vectors_config(VectorParamsBuilder::new(num_features, Distance::Cosine))
-                    .quantization_config(ScalarQuantizationBuilder::default()),
-            )
+            .create_collection(request)
             .await
             .map(|_| self.with_collection(name))
     }
 
-    pub async fn delete_collections(&self, names: &Vec<&str>) {
+    /// Deletes each collection in turn, returning the outcome for every name.
+    pub async fn delete_collections(
+        &self,
+        names: &[&str],
+    ) -> Vec<(String, Result<CollectionOperationResponse, QdrantError>)> {
+        let mut results = Vec::new();
         for collection in names.iter() {
-            if let Err(err) = self.delete_collection(collection).await {
-                println!("Error deleting collection: {}", err);
-            }
+            results.push((
+                collection.to_string(),
+                self.delete_collection(collection).await,
+            ));
         }
+        results
     }
 
     pub async fn delete_collection(
@@ -46,11 +137,13 @@ impl QdrantApi {
         name: &str,
     ) -> Result<CollectionOperationResponse, QdrantError> {
         println!("Deleting collection '{}'", name);
-        self.client.delete_collection(name).await
+        self.client.delete_collection(self.qualify(name)).await
     }
 
     pub fn with_collection(&self, collection: &str) -> WithCollectionBuilder {
-        WithCollectionBuilder::new(&self.client, collection)
+        WithCollectionBuilder::new(&self.client, &self.qualify(collection))
+            .write_options(*self.write_options.read().unwrap())
+            .read_consistency(*self.read_consistency.read().unwrap())
     }
 
     pub async fn list_collections(&self) -> Vec<String> {
@@ -59,11 +152,228 @@ impl QdrantApi {
             .await
             .into_iter()
             .flat_map(|collection| collection.collections.into_iter())
-            .map(|response| response.name)
+            .filter_map(|response| self.unqualify(response.name))
             .collect()
     }
 
+    /// Every alias on the server, as `(alias, collection)` pairs.
+    pub async fn list_aliases(&self) -> Result<Vec<(String, String)>, QdrantError> {
+        self.client.list_aliases().await.map(|response| {
+            response
+                .aliases
+                .into_iter()
+                .filter_map(|alias| {
+                    Some((
+                        self.unqualify(alias.alias_name)?,
+                        self.unqualify(alias.collection_name)?,
+                    ))
+                })
+                .collect()
+        })
+    }
+
+    /// Points `alias` at `collection`. An existing alias with the same name is replaced
+    /// in a single operation, so readers never see the alias missing.
+    pub async fn create_alias(
+        &self,
+        collection: &str,
+        alias: &str,
+    ) -> Result<CollectionOperationResponse, QdrantError> {
+        self.client
+            .create_alias(CreateAliasBuilder::new(
+                self.qualify(collection),
+                self.qualify(alias),
+            ))
+            .await
+    }
+
+    pub async fn collection_exists(&self, name: &str) -> Result<bool, QdrantError> {
+        self.client.collection_exists(self.qualify(name)).await
+    }
+
+    pub async fn collection_info(&self, name: &str) -> Result<Option<CollectionInfo>, QdrantError> {
+        self.client
+            .collection_info(self.qualify(name))
+            .await
+            .map(|response| response.result)
+    }
+
+    /// The vector size the collection was created with, if it uses a single unnamed vector.
+    pub async fn vector_dim(&self, name: &str) -> Result<Option<u64>, QdrantError> {
+        Ok(self
+            .collection_info(name)
+            .await?
+            .as_ref()
+            .and_then(vector_params)
+            .map(|params| params.size))
+    }
+
+    pub async fn create_snapshot(&self, name: &str) -> Result<SnapshotDescription, QdrantError> {
+        self.client
+            .create_snapshot(self.qualify(name))
+            .await?
+            .snapshot_description
+            .ok_or(QdrantError::NoSnapshotFound(name.to_string()))
+    }
+
+    pub async fn list_snapshots(
+        &self,
+        name: &str,
+    ) -> Result<Vec<SnapshotDescription>, QdrantError> {
+        self.client
+            .list_snapshots(self.qualify(name))
+            .await
+            .map(|response| response.snapshot_descriptions)
+    }
+
+    /// Downloads the snapshot over the REST API, which gRPC does not expose.
+    pub async fn download_snapshot(
+        &self,
+        name: &str,
+        snapshot_name: &str,
+        out_path: &Path,
+    ) -> Result<(), QdrantError> {
+        self.client
+            .download_snapshot(
+                SnapshotDownloadBuilder::new(out_path, self.qualify(name))
+                    .snapshot_name(snapshot_name)
+                    .rest_api_uri(self.rest_url.as_str()),
+            )
+            .await
+    }
+
+    /// Recovers the collection from a snapshot the server can read: an `http(s)://` URL,
+    /// or a `file://` path inside the server's snapshots directory. The collection is
+    /// created if it doesn't exist, and replaced if it does.
+    pub async fn restore_snapshot(&self, name: &str, location: &str) -> Result<(), QdrantError> {
+        let url = format!(
+            "{}/collections/{}/snapshots/recover?wait=true",
+            self.rest_url,
+            self.qualify(name)
+        );
+        let response = reqwest::Client::new()
+            .put(&url)
+            .json(&serde_json::json!({ "location": location }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.text().await.unwrap_or_default();
+        let code = match status.as_u16() {
+            400 | 422 => tonic::Code::InvalidArgument,
+            404 => tonic::Code::NotFound,
+            409 => tonic::Code::AlreadyExists,
+            _ => tonic::Code::Unknown,
+        };
+        Err(QdrantError::ResponseError {
+            status: tonic::Status::new(code, format!("{}: {}", status, body)),
+        })
+    }
+
     pub async fn health_check(&self)->Result<HealthCheckReply,QdrantError>{
         self.client.health_check().await
     }
 }
+
+/// The REST URL of the server behind a gRPC URL: the default gRPC port is swapped for
+/// the default REST port, any other URL is assumed to serve both.
+fn rest_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    match url.strip_suffix(DEFAULT_GRPC_PORT) {
+        Some(host) => format!("{}{}", host, DEFAULT_REST_PORT),
+        None => url.to_string(),
+    }
+}
+
+/// Whether the error is Qdrant refusing to create something that already exists.
+pub fn is_already_exists(err: &QdrantError) -> bool {
+    match err {
+        QdrantError::ResponseError { status } => {
+            status.code() == tonic::Code::AlreadyExists
+                || status.message().contains("already exists")
+        }
+        _ => false,
+    }
+}
+
+/// The parameters of the collection's single unnamed vector, if it has one.
+pub fn vector_params(info: &CollectionInfo) -> Option<&VectorParams> {
+    let vectors_config = info
+        .config
+        .as_ref()?
+        .params
+        .as_ref()?
+        .vectors_config
+        .as_ref()?;
+    match vectors_config.config.as_ref()? {
+        Config::Params(params) => Some(params),
+        Config::ParamsMap(_) => None,
+    }
+}
+
+fn quantization(quantization: &QuantizationType) -> Option<Quantization> {
+    match quantization {
+        QuantizationType::None => None,
+        QuantizationType::Scalar => Some(ScalarQuantizationBuilder::default().into()),
+        QuantizationType::Product(compression) => {
+            Some(ProductQuantizationBuilder::new(*compression as i32).into())
+        }
+        QuantizationType::Binary { always_ram } => {
+            Some(BinaryQuantizationBuilder::new(*always_ram).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "http://localhost:6334";
+
+    #[test]
+    fn qualifies_with_the_namespace() {
+        let api = QdrantApi::new(URL, Some("app"));
+        assert_eq!(api.qualify("files"), "app__files");
+
+        let api = QdrantApi::new(URL, None);
+        assert_eq!(api.qualify("files"), "files");
+    }
+
+    #[test]
+    fn unqualify_reverses_qualify() {
+        let api = QdrantApi::new(URL, Some("app"));
+        assert_eq!(
+            api.unqualify(api.qualify("my__files")),
+            Some("my__files".to_string())
+        );
+
+        let api = QdrantApi::new(URL, None);
+        assert_eq!(
+            api.unqualify("files".to_string()),
+            Some("files".to_string())
+        );
+    }
+
+    #[test]
+    fn unqualify_ignores_other_namespaces() {
+        let api = QdrantApi::new(URL, Some("app"));
+        assert_eq!(api.unqualify("files".to_string()), None);
+        assert_eq!(api.unqualify("other__files".to_string()), None);
+        // A namespace that starts with this one is still a different namespace
+        assert_eq!(api.unqualify("app2__files".to_string()), None);
+        assert_eq!(api.unqualify("app_files".to_string()), None);
+    }
+
+    #[test]
+    fn rest_url_swaps_the_default_grpc_port() {
+        assert_eq!(rest_url("http://localhost:6334"), "http://localhost:6333");
+        assert_eq!(rest_url("http://localhost:6334/"), "http://localhost:6333");
+        assert_eq!(
+            rest_url("https://qdrant.example.com"),
+            "https://qdrant.example.com"
+        );
+    }
+}
@@ -1,14 +1,32 @@
 use std::collections::HashMap;
 
-use qdrant_client::qdrant::{DeletePointsBuilder, PointStruct, PointsIdsList, UpsertPointsBuilder};
-use qdrant_client::qdrant::{PointsOperationResponse, SearchPointsBuilder};
-use qdrant_client::{Qdrant, QdrantError};
+use qdrant_client::qdrant::{
+    group_id::Kind, point_id::PointIdOptions, vectors::VectorsOptions, CountPointsBuilder,
+    DeletePointsBuilder, Filter, GetPointsBuilder, GroupId, PointId, PointStruct, PointsIdsList,
+    PointsOperationResponse, PointsUpdateOperation, RecommendPointsBuilder, ScoredPoint,
+    ScrollPointsBuilder, SearchBatchPointsBuilder, SearchPointGroupsBuilder, SearchPointsBuilder,
+    UpdateBatchPointsBuilder, UpdateBatchResponse, UpsertPointsBuilder, Vectors,
+};
+use qdrant_client::qdrant::{
+    points_selector::PointsSelectorOneOf, read_consistency, CreateFieldIndexCollectionBuilder,
+    DeletePayloadPointsBuilder, FieldType, ReadConsistencyType, SetPayloadPointsBuilder,
+    WriteOrdering,
+};
+use qdrant_client::{Payload, Qdrant, QdrantError};
+
+use crate::indexer_api::models::consistency_models::WriteOptions;
 
 type EmbeddingResult = (HashMap<String, qdrant_client::qdrant::Value>, f32);
+type GroupResult = (String, Vec<EmbeddingResult>);
+type PayloadMap = HashMap<String, qdrant_client::qdrant::Value>;
+type VectorResult = (PayloadMap, f32, Embeddings);
+type PointRecord = (u64, Embeddings, PayloadMap);
 pub type Embeddings = Vec<f32>;
 pub struct WithCollectionBuilder<'a> {
     client: &'a Qdrant,
     collection: String,
+    write_options: WriteOptions,
+    read_consistency: Option<ReadConsistencyType>,
 }
 
 impl<'a> WithCollectionBuilder<'a> {
@@ -16,16 +34,71 @@ impl<'a> WithCollectionBuilder<'a> {
         Self {
             client,
             collection: collection.to_string(),
+            write_options: WriteOptions::default(),
+            read_consistency: None,
         }
     }
 
-    pub async fn insert_many<T>(&self, data: Vec<(Embeddings, T, u64)>)
+    /// Sets `wait` and the write ordering of every write made through this builder.
+    pub fn write_options(mut self, write_options: WriteOptions) -> Self {
+        self.write_options = write_options;
+        self
+    }
+
+    /// Sets the read consistency of every read made through this builder.
+    /// `None` leaves it to Qdrant.
+    pub fn read_consistency(mut self, read_consistency: Option<ReadConsistencyType>) -> Self {
+        self.read_consistency = read_consistency;
+        self
+    }
+
+    pub async fn create_field_index(
+        &self,
+        field: &str,
+        field_type: FieldType,
+    ) -> Result<PointsOperationResponse, QdrantError> {
+        self.client
+            .create_field_index(
+                CreateFieldIndexCollectionBuilder::new(&self.collection, field, field_type)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
+    }
+
+    /// Upserts the points one at a time. Returns the id and error of every point that failed.
+    pub async fn insert_many<T>(&self, data: Vec<(Embeddings, T, u64)>) -> Vec<(u64, QdrantError)>
     where
         T: std::convert::Into<qdrant_client::Payload>,
     {
+        let mut failures = Vec::new();
         for (embedding, payload, id) in data.into_iter() {
-            _ = self.insert(embedding, payload, id).await;
+            if let Err(err) = self.insert(embedding, payload, id).await {
+                failures.push((id, err));
+            }
         }
+        failures
+    }
+
+    /// Upserts all of the points in a single request.
+    pub async fn insert_batch<T>(
+        &self,
+        data: Vec<(Embeddings, T, u64)>,
+    ) -> Result<PointsOperationResponse, QdrantError>
+    where
+        T: std::convert::Into<qdrant_client::Payload>,
+    {
+        let points: Vec<PointStruct> = data
+            .into_iter()
+            .map(|(embeddings, payload, id)| PointStruct::new(id, embeddings, payload))
+            .collect();
+        self.client
+            .upsert_points(
+                UpsertPointsBuilder::new(&self.collection, points)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
     }
 
     pub async fn insert<T>(
@@ -44,7 +117,11 @@ impl<'a> WithCollectionBuilder<'a> {
             payload,
         )];
         self.client
-            .upsert_points(UpsertPointsBuilder::new(&self.collection, points))
+            .upsert_points(
+                UpsertPointsBuilder::new(&self.collection, points)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
             .await
     }
 
@@ -55,7 +132,82 @@ impl<'a> WithCollectionBuilder<'a> {
     pub async fn remove_many(&self, ids:Vec<u64>)->Result<PointsOperationResponse, QdrantError>{
         self.client.delete_points(DeletePointsBuilder::new(&self.collection).points(PointsIdsList{
             ids: ids.into_iter().map(|x|x.into()).collect()
-        })).await
+        }).wait(self.write_options.wait).ordering(self.ordering())).await
+    }
+
+    pub async fn remove_by_filter(
+        &self,
+        filter: Filter,
+    ) -> Result<PointsOperationResponse, QdrantError> {
+        self.client
+            .delete_points(
+                DeletePointsBuilder::new(&self.collection)
+                    .points(filter)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
+    }
+
+    /// Applies the operations in order in a single request.
+    pub async fn update_batch(
+        &self,
+        operations: Vec<PointsUpdateOperation>,
+    ) -> Result<UpdateBatchResponse, QdrantError> {
+        self.client
+            .update_points_batch(
+                UpdateBatchPointsBuilder::new(&self.collection, operations)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
+    }
+
+    /// Merges `payload` into the payload of the selected points, keeping their vectors.
+    pub async fn set_payload(
+        &self,
+        selector: PointsSelectorOneOf,
+        payload: Payload,
+    ) -> Result<PointsOperationResponse, QdrantError> {
+        self.client
+            .set_payload(
+                SetPayloadPointsBuilder::new(&self.collection, payload)
+                    .points_selector(selector)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
+    }
+
+    /// Replaces the whole payload of the selected points with `payload`, keeping their vectors.
+    pub async fn overwrite_payload(
+        &self,
+        selector: PointsSelectorOneOf,
+        payload: Payload,
+    ) -> Result<PointsOperationResponse, QdrantError> {
+        self.client
+            .overwrite_payload(
+                SetPayloadPointsBuilder::new(&self.collection, payload)
+                    .points_selector(selector)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
+    }
+
+    pub async fn delete_payload_keys(
+        &self,
+        selector: PointsSelectorOneOf,
+        keys: Vec<String>,
+    ) -> Result<PointsOperationResponse, QdrantError> {
+        self.client
+            .delete_payload(
+                DeletePayloadPointsBuilder::new(&self.collection, keys)
+                    .points_selector(selector)
+                    .wait(self.write_options.wait)
+                    .ordering(self.ordering()),
+            )
+            .await
     }
 
     pub async fn search(
@@ -63,8 +215,11 @@ impl<'a> WithCollectionBuilder<'a> {
         embedding: Embeddings,
         top_k: u64,
     ) -> Result<Vec<EmbeddingResult>, QdrantError> {
-        let search_request =
+        let mut search_request =
             SearchPointsBuilder::new(&self.collection, embedding, top_k).with_payload(true);
+        if let Some(read_consistency) = self.read_consistency_value() {
+            search_request = search_request.read_consistency(read_consistency);
+        }
 
         self.client
             .search_points(search_request)
@@ -80,4 +235,284 @@ impl<'a> WithCollectionBuilder<'a> {
                     .collect()
             })
     }
+
+    /// Same as `search`, but also returns the stored vector of every hit.
+    /// Hits without a dense vector are skipped.
+    pub async fn search_with_vectors(
+        &self,
+        embedding: Embeddings,
+        top_k: u64,
+    ) -> Result<Vec<VectorResult>, QdrantError> {
+        let mut search_request = SearchPointsBuilder::new(&self.collection, embedding, top_k)
+            .with_payload(true)
+            .with_vectors(true);
+        if let Some(read_consistency) = self.read_consistency_value() {
+            search_request = search_request.read_consistency(read_consistency);
+        }
+
+        self.client
+            .search_points(search_request)
+            .await
+            .map(|response| {
+                response
+                    .result
+                    .into_iter()
+                    .filter_map(|result| {
+                        let vector = dense_vector(result.vectors)?;
+                        Some((result.payload, result.score, vector))
+                    })
+                    .collect()
+            })
+    }
+
+    /// Runs one search per embedding in a single request.
+    /// The results are in the same order as `embeddings`.
+    pub async fn search_batch(
+        &self,
+        embeddings: Vec<Embeddings>,
+        top_k: u64,
+    ) -> Result<Vec<Vec<EmbeddingResult>>, QdrantError> {
+        let searches: Vec<_> = embeddings
+            .into_iter()
+            .map(|embedding| {
+                SearchPointsBuilder::new(&self.collection, embedding, top_k)
+                    .with_payload(true)
+                    .build()
+            })
+            .collect();
+        let mut request = SearchBatchPointsBuilder::new(&self.collection, searches);
+        if let Some(read_consistency) = self.read_consistency_value() {
+            request = request.read_consistency(read_consistency);
+        }
+
+        self.client
+            .search_batch_points(request)
+            .await
+            .map(|response| {
+                response
+                    .result
+                    .into_iter()
+                    .map(|batch| batch.result.into_iter().map(to_embedding_result).collect())
+                    .collect()
+            })
+    }
+
+    /// Searches and groups the hits by the value of the `group_by` payload field.
+    /// At most `groups` groups are returned, each with up to `group_size` hits.
+    pub async fn search_groups(
+        &self,
+        embedding: Embeddings,
+        group_by: &str,
+        group_size: u32,
+        groups: u32,
+    ) -> Result<Vec<GroupResult>, QdrantError> {
+        let mut search_request = SearchPointGroupsBuilder::new(
+            &self.collection,
+            embedding,
+            groups,
+            group_by,
+            group_size,
+        )
+        .with_payload(true);
+        if let Some(read_consistency) = self.read_consistency_value() {
+            search_request = search_request.read_consistency(read_consistency);
+        }
+
+        self.client
+            .search_groups(search_request)
+            .await
+            .map(|response| {
+                response
+                    .result
+                    .into_iter()
+                    .flat_map(|result| result.groups.into_iter())
+                    .map(|group| {
+                        (
+                            group_key(group.id),
+                            group.hits.into_iter().map(to_embedding_result).collect(),
+                        )
+                    })
+                    .collect()
+            })
+    }
+
+    /// Fetches the payloads of the given points.
+    /// Points that don't exist are left out of the result.
+    pub async fn get_many(&self, ids: Vec<u64>) -> Result<Vec<(u64, PayloadMap)>, QdrantError> {
+        let mut request = GetPointsBuilder::new(
+            &self.collection,
+            ids.into_iter().map(|x| x.into()).collect::<Vec<PointId>>(),
+        )
+        .with_payload(true);
+        if let Some(read_consistency) = self.read_consistency_value() {
+            request = request.read_consistency(read_consistency);
+        }
+
+        self.client.get_points(request).await.map(|response| {
+            response
+                .result
+                .into_iter()
+                .filter_map(|point| match point.id?.point_id_options? {
+                    PointIdOptions::Num(id) => Some((id, point.payload)),
+                    PointIdOptions::Uuid(_) => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Counts the points matching `filter`. An exact count is slower than an approximate one.
+    pub async fn count(&self, filter: Option<Filter>, exact: bool) -> Result<u64, QdrantError> {
+        let mut request = CountPointsBuilder::new(&self.collection).exact(exact);
+        if let Some(filter) = filter {
+            request = request.filter(filter);
+        }
+        if let Some(read_consistency) = self.read_consistency_value() {
+            request = request.read_consistency(read_consistency);
+        }
+
+        self.client.count(request).await.map(|response| {
+            response
+                .result
+                .map(|result| result.count)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Reads one page of up to `limit` points, starting at `offset`.
+    /// Returns the payloads and the offset of the next page, if there is one.
+    pub async fn scroll(
+        &self,
+        filter: Option<Filter>,
+        limit: u32,
+        offset: Option<PointId>,
+    ) -> Result<(Vec<PayloadMap>, Option<PointId>), QdrantError> {
+        let mut request = ScrollPointsBuilder::new(&self.collection)
+            .limit(limit)
+            .with_payload(true);
+        if let Some(filter) = filter {
+            request = request.filter(filter);
+        }
+        if let Some(offset) = offset {
+            request = request.offset(offset);
+        }
+        if let Some(read_consistency) = self.read_consistency_value() {
+            request = request.read_consistency(read_consistency);
+        }
+
+        self.client.scroll(request).await.map(|response| {
+            (
+                response
+                    .result
+                    .into_iter()
+                    .map(|point| point.payload)
+                    .collect(),
+                response.next_page_offset,
+            )
+        })
+    }
+
+    /// Same as `scroll`, but returns the id and vector of every point along with its payload.
+    /// Fails on points it can't represent, those with a UUID id or named vectors, rather
+    /// than skipping them.
+    pub async fn scroll_with_vectors(
+        &self,
+        filter: Option<Filter>,
+        limit: u32,
+        offset: Option<PointId>,
+    ) -> Result<(Vec<PointRecord>, Option<PointId>), QdrantError> {
+        let mut request = ScrollPointsBuilder::new(&self.collection)
+            .limit(limit)
+            .with_payload(true)
+            .with_vectors(true);
+        if let Some(filter) = filter {
+            request = request.filter(filter);
+        }
+        if let Some(offset) = offset {
+            request = request.offset(offset);
+        }
+        if let Some(read_consistency) = self.read_consistency_value() {
+            request = request.read_consistency(read_consistency);
+        }
+
+        let response = self.client.scroll(request).await?;
+        let mut points = Vec::with_capacity(response.result.len());
+        for point in response.result {
+            let id = match point.id.and_then(|id| id.point_id_options) {
+                Some(PointIdOptions::Num(id)) => id,
+                Some(PointIdOptions::Uuid(uuid)) => {
+                    let message = format!("point '{}' has a UUID id", uuid);
+                    return Err(QdrantError::ConversionError(message));
+                }
+                None => {
+                    let message = "point without an id".to_string();
+                    return Err(QdrantError::ConversionError(message));
+                }
+            };
+            let Some(vector) = dense_vector(point.vectors) else {
+                let message = format!("point {} has no single dense vector", id);
+                return Err(QdrantError::ConversionError(message));
+            };
+            points.push((id, vector, point.payload));
+        }
+        Ok((points, response.next_page_offset))
+    }
+
+    /// Finds points similar to the `positive` points and dissimilar to the `negative` ones,
+    /// using the vectors already stored for those points.
+    pub async fn recommend(
+        &self,
+        positive: Vec<u64>,
+        negative: Vec<u64>,
+        top_k: u64,
+    ) -> Result<Vec<EmbeddingResult>, QdrantError> {
+        let mut request = RecommendPointsBuilder::new(&self.collection, top_k).with_payload(true);
+        for id in positive {
+            request = request.add_positive(id);
+        }
+        for id in negative {
+            request = request.add_negative(id);
+        }
+        if let Some(read_consistency) = self.read_consistency_value() {
+            request = request.read_consistency(read_consistency);
+        }
+
+        self.client.recommend(request).await.map(|response| {
+            response
+                .result
+                .into_iter()
+                .map(to_embedding_result)
+                .collect()
+        })
+    }
+
+    fn ordering(&self) -> WriteOrdering {
+        WriteOrdering {
+            r#type: self.write_options.ordering as i32,
+        }
+    }
+
+    fn read_consistency_value(&self) -> Option<read_consistency::Value> {
+        self.read_consistency
+            .map(|read_consistency| read_consistency::Value::Type(read_consistency as i32))
+    }
+}
+
+fn to_embedding_result(point: ScoredPoint) -> EmbeddingResult {
+    (point.payload, point.score)
+}
+
+fn group_key(id: Option<GroupId>) -> String {
+    match id.and_then(|id| id.kind) {
+        Some(Kind::UnsignedValue(value)) => value.to_string(),
+        Some(Kind::IntegerValue(value)) => value.to_string(),
+        Some(Kind::StringValue(value)) => value,
+        None => String::new(),
+    }
+}
+
+fn dense_vector(vectors: Option<Vectors>) -> Option<Embeddings> {
+    match vectors?.vectors_options? {
+        VectorsOptions::Vector(vector) => vector.try_into_dense().ok(),
+        VectorsOptions::Vectors(_) => None,
+    }
 }
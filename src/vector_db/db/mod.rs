@@ -1,4 +1,4 @@
 pub mod api;
-mod builders{
+pub(crate) mod builders{
     pub mod with_collection;
 }
\ No newline at end of file
@@ -2,20 +2,27 @@ use fastembed::{EmbeddingModel, Error, InitOptions, TextEmbedding};
 
 type Embeddings = Vec<f32>;
 
+const MODEL: EmbeddingModel = EmbeddingModel::AllMiniLML6V2;
+/// Recorded in the registry for every collection, so it must not change while `MODEL` doesn't.
+/// Spelled out rather than derived from fastembed, whose names may change between versions.
+const MODEL_ID: &str = "sentence-transformers/all-MiniLM-L6-v2";
+
 pub struct EmbeddingsGenerator {
     model: TextEmbedding,
     pub embedding_dim_len: u64,
+    /// Identifies the embedding model, so collections can record which model filled them
+    pub model_id: String,
 }
 
 impl EmbeddingsGenerator {
     pub fn new() -> Self {
-        let model = TextEmbedding::try_new(
-            InitOptions::new(EmbeddingModel::AllMiniLML6V2).with_show_download_progress(true),
-        )
-        .unwrap();
+        let model =
+            TextEmbedding::try_new(InitOptions::new(MODEL).with_show_download_progress(true))
+                .unwrap();
         Self {
             model,
             embedding_dim_len: 384,
+            model_id: MODEL_ID.to_string(),
         }
     }
 
@@ -0,0 +1,34 @@
+use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+
+use crate::indexer_api::traits::reranker::Reranker;
+
+/// Cross-encoder re-ranker backed by a fastembed `TextRerank` model
+pub struct FastembedReranker {
+    model: TextRerank,
+}
+
+impl FastembedReranker {
+    pub fn new(model: RerankerModel) -> Result<Self, String> {
+        let model = TextRerank::try_new(
+            RerankInitOptions::new(model).with_show_download_progress(true),
+        )
+        .map_err(|err| format!("Error loading reranker model: {}", err))?;
+        Ok(Self { model })
+    }
+}
+
+impl Reranker for FastembedReranker {
+    fn rerank(&self, query: &str, documents: Vec<&str>) -> Result<Vec<f32>, String> {
+        let mut scores = vec![0.0; documents.len()];
+        let results = self
+            .model
+            .rerank(query, documents, false, None)
+            .map_err(|err| format!("Error reranking documents: {}", err))?;
+
+        // fastembed returns the results sorted by score, so put them back in input order
+        for result in results {
+            scores[result.index] = result.score;
+        }
+        Ok(scores)
+    }
+}